@@ -48,6 +48,16 @@ pub fn bench_bump(c: &mut Criterion) {
     });
 }
 
+pub fn bench_bump_with_granularity(c: &mut Criterion) {
+    c.bench_function("Bump::with_granularity", |b| {
+        b.iter(|| {
+            let mut buf = [0; 128 * 1024];
+            let bump = Bump::with_granularity(&mut buf, 16);
+            bench_allocator(&bump);
+        });
+    });
+}
+
 pub fn bench_atomic_bump(c: &mut Criterion) {
     c.bench_function("AtomicBump", |b| {
         b.iter(|| {
@@ -58,5 +68,11 @@ pub fn bench_atomic_bump(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_system, bench_bump, bench_atomic_bump);
+criterion_group!(
+    benches,
+    bench_system,
+    bench_bump,
+    bench_bump_with_granularity,
+    bench_atomic_bump
+);
 criterion_main!(benches);
@@ -63,6 +63,21 @@
 //! drop(b);
 //! let b: Box<u8, &Bump> = Box::try_new_in(2, &bump).unwrap();
 //! ```
+//!
+//! Mutable references:
+//!
+//! `core` implements [`Allocator`] for `&mut A` wherever `A: Allocator`,
+//! so code generic over an allocator can be handed a `&mut Bump` too.
+//!
+//! ```
+//! #![feature(allocator_api)]
+//!
+//! use qbump::{static_buf, Bump};
+//!
+//! let mut bump = Bump::new(static_buf!([u8; 4]));
+//! let b: Box<u8, &mut Bump> = Box::try_new_in(1, &mut bump).unwrap();
+//! assert_eq!(*b, 1);
+//! ```
 
 #![no_std]
 #![feature(allocator_api)]
@@ -75,11 +90,12 @@ extern crate alloc;
 use core::cell::{Cell, UnsafeCell};
 use core::intrinsics;
 use core::marker::PhantomData;
-use core::mem::MaybeUninit;
+use core::mem::{self, MaybeUninit};
 use core::ptr::{self, NonNull};
 use core::sync::atomic::{self, AtomicPtr, AtomicUsize, Ordering::*};
 
 use alloc::alloc::{AllocError, Allocator, GlobalAlloc, Layout};
+use alloc::boxed::Box;
 
 /// A single threaded bump allocator.
 pub struct Bump<'a> {
@@ -87,20 +103,61 @@ pub struct Bump<'a> {
     upper: *mut u8,
     head: Cell<*mut u8>,
     count: Cell<usize>,
+    min_align: usize,
+    granularity: usize,
+    #[cfg(feature = "stats")]
+    padding: Cell<usize>,
 
     _marker: PhantomData<&'a ()>,
 }
 
+/// A copyable handle to a [`Bump`] allocator.
+///
+/// The handle implements [`Allocator`] by forwarding to the allocator it
+/// was created from, so it can be stored by value in as many collections
+/// as needed (e.g., `Box<T, BumpHandle>`), just like `&Bump`. Create one
+/// with [`Bump::handle`].
+#[derive(Clone, Copy)]
+pub struct BumpHandle<'a> {
+    bump: &'a Bump<'a>,
+}
+
 /// A thread-safe atomic bump allocator.
 pub struct AtomicBump<'a> {
     lower: *mut u8,
     upper: *mut u8,
     head: AtomicPtr<u8>,
     count: AtomicUsize,
+    min_align: usize,
 
     _marker: PhantomData<&'a ()>,
 }
 
+/// A single threaded bump allocator which detects double frees.
+///
+/// The addresses of live allocations are recorded in a caller provided
+/// table. Deallocating a pointer which is not live (e.g., freeing the same
+/// pointer twice) panics.
+///
+/// Allocations made while the table is full are not tracked; until they are
+/// freed, deallocating an unknown pointer cannot be reported.
+///
+/// This is a debugging aid; prefer [`Bump`] in production.
+pub struct TrackedBump<'a, 't> {
+    bump: Bump<'a>,
+    live: &'t [Cell<usize>],
+    untracked: Cell<usize>,
+}
+
+/// A single threaded bump allocator which zeroes memory on deallocation.
+///
+/// Freed regions are wiped with volatile writes so that secrets (e.g., keys
+/// or tokens) are not left behind for the next allocation to read. This is
+/// slower than [`Bump`], which leaves freed memory untouched.
+pub struct ZeroizeBump<'a> {
+    bump: Bump<'a>,
+}
+
 /// A global single threaded bump allocator.
 pub struct GlobalBump<const N: usize> {
     buf: UnsafeCell<[u8; N]>,
@@ -144,6 +201,107 @@ impl<'a> Bump<'a> {
         unsafe { Self::from_ptr(buf.as_mut_ptr(), buf.len()) }
     }
 
+    /// Creates a new bump allocator which aligns every allocation to at
+    /// least `min_align` bytes.
+    ///
+    /// This is useful to stop allocations sharing a cache line, at the
+    /// cost of padding between small allocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_align` is not a power of two.
+    pub fn with_min_align(buf: &'a mut [u8], min_align: usize) -> Self {
+        assert!(min_align.is_power_of_two());
+        Self {
+            min_align,
+            ..Self::new(buf)
+        }
+    }
+
+    /// Creates a new bump allocator which rounds the size of every
+    /// allocation up to a multiple of `granularity` bytes.
+    ///
+    /// Growing an allocation within its rounded size (e.g., a `Vec`
+    /// reserving more capacity) is then done in place, rather than
+    /// abandoning the old block and bumping a new one. This trades some
+    /// wasted space for less fragmentation of the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `granularity` is not a power of two.
+    pub fn with_granularity(buf: &'a mut [u8], granularity: usize) -> Self {
+        assert!(granularity.is_power_of_two());
+        Self {
+            granularity,
+            ..Self::new(buf)
+        }
+    }
+
+    /// Splits a buffer into independent allocators, one per entry in
+    /// `sizes`.
+    ///
+    /// Each allocator is given the next `sizes[i]` bytes of the buffer, so
+    /// exhausting one does not affect the others. Bytes left over after the
+    /// last allocator are unused.
+    ///
+    /// Returns `None` if the sizes add up to more than the buffer.
+    ///
+    /// ```
+    /// #![feature(allocator_api)]
+    ///
+    /// use qbump::{static_buf, Bump};
+    ///
+    /// let [net, ui] = Bump::split(static_buf!([u8; 128]), [96, 32]).unwrap();
+    /// assert!(Box::try_new_in([0_u8; 64], &net).is_ok());
+    /// assert!(Box::try_new_in([0_u8; 64], &ui).is_err());
+    /// ```
+    pub fn split<const N: usize>(buf: &'a mut [u8], sizes: [usize; N]) -> Option<[Self; N]> {
+        let total = sizes
+            .iter()
+            .try_fold(0_usize, |acc, n| acc.checked_add(*n))?;
+        if total > buf.len() {
+            return None;
+        }
+
+        let mut rest = buf;
+        Some(core::array::from_fn(|i| {
+            let (head, tail) = mem::take(&mut rest).split_at_mut(sizes[i]);
+            rest = tail;
+            Self::new(head)
+        }))
+    }
+
+    /// Re-points the allocator at a new buffer (e.g., a larger one).
+    ///
+    /// The minimum alignment and granularity of the allocator are kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any allocations are still live.
+    pub fn rebind(&mut self, buf: &'a mut [u8]) {
+        assert_eq!(self.count(), 0, "[rebind] :: allocations are still live");
+        *self = Self {
+            min_align: self.min_align,
+            granularity: self.granularity,
+            ..Self::new(buf)
+        };
+    }
+
+    /// Releases every allocation, making the whole buffer available again.
+    ///
+    /// A bump allocator only reclaims its buffer once the count returns to
+    /// 0, so a single leaked allocation (e.g., a `mem::forget`-ed `Box`)
+    /// otherwise pins everything allocated before it.
+    ///
+    /// Exclusive access proves that nothing still borrows the allocator, so
+    /// any allocation which was not deallocated has been leaked; blocks from
+    /// an allocator are only valid while it is borrowed, so they can safely
+    /// be reused. Allocations are never relocated.
+    pub fn compact(&mut self) {
+        self.head.set(self.upper);
+        self.count.set(0);
+    }
+
     /// How many allocations has this allocator created?
     ///
     /// Once all buffers served by the allocator are deallocated the
@@ -152,6 +310,158 @@ impl<'a> Bump<'a> {
     pub fn count(&self) -> usize {
         self.count.get()
     }
+
+    /// The `[lower, upper)` address range managed by this allocator.
+    #[inline]
+    pub fn bounds(&self) -> (*const u8, *const u8) {
+        (self.lower, self.upper)
+    }
+
+    /// Does `ptr` lie within the buffer managed by this allocator?
+    ///
+    /// This only checks `ptr` against [`bounds`](Self::bounds); it does not
+    /// track whether the allocation is still live.
+    #[inline]
+    pub fn owns(&self, ptr: *const u8) -> bool {
+        (self.lower.addr()..self.upper.addr()).contains(&ptr.addr())
+    }
+
+    /// How many bytes have been skipped to align allocations?
+    ///
+    /// This is a running total over every allocation made since the
+    /// allocator was created, and is not reset when the buffer is reused.
+    /// Space lost to [`Bump::with_granularity`] rounding is not included.
+    ///
+    /// Only available with the `stats` feature.
+    #[cfg(feature = "stats")]
+    #[inline]
+    pub fn padding_bytes(&self) -> usize {
+        self.padding.get()
+    }
+
+    /// Creates a copyable handle to the allocator.
+    ///
+    /// ```
+    /// #![feature(allocator_api)]
+    ///
+    /// use qbump::{static_buf, Bump, BumpHandle};
+    ///
+    /// struct Frames<'a> {
+    ///     alloc: BumpHandle<'a>,
+    /// }
+    ///
+    /// let bump = Bump::new(static_buf!([u8; 8]));
+    /// let frames = Frames { alloc: bump.handle() };
+    /// let b = Box::try_new_in(1_u32, frames.alloc).unwrap();
+    /// assert_eq!(bump.count(), 1);
+    /// ```
+    #[inline]
+    pub fn handle(&self) -> BumpHandle<'_> {
+        BumpHandle { bump: self }
+    }
+
+    /// Allocates uninitialized storage for a `T`, e.g., to be filled by DMA
+    /// or written piecewise.
+    ///
+    /// The storage is never deallocated, so it counts towards
+    /// [`Bump::count`] until the allocator is [compacted](Bump::compact).
+    ///
+    /// Returns `None` if there is not enough space left in the buffer.
+    ///
+    /// ```
+    /// #![feature(allocator_api)]
+    ///
+    /// use qbump::{static_buf, Bump};
+    ///
+    /// let bump = Bump::new(static_buf!([u8; 8]));
+    /// let x = bump.alloc_uninit::<u32>().unwrap();
+    /// assert_eq!(*x.write(123), 123);
+    /// ```
+    // each call hands out a distinct allocation
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_uninit<T>(&self) -> Option<&mut MaybeUninit<T>> {
+        self.alloc_uninit_slice(1)?.first_mut()
+    }
+
+    /// Allocates uninitialized storage for `n` contiguous values of `T`.
+    ///
+    /// See [`Bump::alloc_uninit`] for more information.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_uninit_slice<T>(&self, n: usize) -> Option<&mut [MaybeUninit<T>]> {
+        let layout = Layout::array::<T>(n).ok()?;
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            self.allocate(layout).ok()?.cast::<MaybeUninit<T>>()
+        };
+
+        // safety: `ptr` is aligned and valid for `n` values, and is not
+        // handed out again while the allocator is borrowed
+        Some(unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr(), n) })
+    }
+
+    /// Allocates uninitialized storage for as many contiguous values of `T`
+    /// as fit in the space left in the buffer.
+    ///
+    /// After this call the allocator is full for `T`; smaller or less
+    /// strictly aligned allocations may still fit in the padding left over.
+    ///
+    /// Returns `None` if not even one `T` fits, or if `T` is zero sized.
+    ///
+    /// ```
+    /// #![feature(allocator_api)]
+    ///
+    /// use qbump::{static_buf, Bump};
+    ///
+    /// let bump = Bump::new(static_buf!([u8; 64]));
+    /// let _ = bump.alloc_uninit::<[u8; 16]>().unwrap();
+    /// let samples = bump.remaining_slab::<[u8; 12]>().unwrap();
+    /// assert_eq!(samples.len(), 4);
+    /// assert!(bump.remaining_slab::<[u8; 12]>().is_none());
+    /// ```
+    #[allow(clippy::mut_from_ref)]
+    pub fn remaining_slab<T>(&self) -> Option<&mut [MaybeUninit<T>]> {
+        let size = mem::size_of::<T>();
+        if size == 0 {
+            return None;
+        }
+
+        // the slab fits iff the head lands at or above the lowest address
+        // aligned for `T`, with the block rounded up to the granularity
+        let align = mem::align_of::<T>().max(self.min_align);
+        let lower = self.lower.addr().checked_add(align - 1)? & !(align - 1);
+        let avail = self.head.get().addr().checked_sub(lower)?;
+        let n = (avail & !(self.granularity - 1)) / size;
+        if n == 0 {
+            return None;
+        }
+
+        self.alloc_uninit_slice(n)
+    }
+
+    /// Allocates a slab of `n` contiguous default initialized values.
+    ///
+    /// The slab is a single allocation, so unlike allocating each value
+    /// individually the values are laid out in ascending address order.
+    ///
+    /// Returns `None` if there is not enough space left in the buffer.
+    pub fn slab<T: Default>(&self, n: usize) -> Option<Box<[T], &Self>> {
+        let layout = Layout::array::<T>(n).ok()?;
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            self.allocate(layout).ok()?.cast::<T>()
+        };
+
+        for i in 0..n {
+            // safety: `ptr` is valid for writes of `n` values
+            unsafe { ptr.as_ptr().add(i).write(T::default()) };
+        }
+
+        let slab = ptr::slice_from_raw_parts_mut(ptr.as_ptr(), n);
+        // safety: `slab` was allocated by `self` with the layout of `[T]`
+        Some(unsafe { Box::from_raw_in(slab, self) })
+    }
 }
 
 impl Bump<'_> {
@@ -173,9 +483,19 @@ impl Bump<'_> {
             upper,
             head: Cell::new(upper),
             count: Cell::new(0),
+            min_align: 1,
+            granularity: 1,
+            #[cfg(feature = "stats")]
+            padding: Cell::new(0),
             _marker: PhantomData,
         }
     }
+
+    /// The size of the block served for a request of `size` bytes.
+    #[inline]
+    fn rounded(&self, size: usize) -> Option<usize> {
+        Some(size.checked_add(self.granularity - 1)? & !(self.granularity - 1))
+    }
 }
 
 unsafe impl Allocator for Bump<'_> {
@@ -184,10 +504,11 @@ unsafe impl Allocator for Bump<'_> {
             return Ok(NonNull::slice_from_raw_parts(NonNull::dangling(), 0));
         }
 
+        let size = self.rounded(layout.size()).ok_or(AllocError)?;
+        let align = layout.align().max(self.min_align);
         let head = self.head.get();
-        let new_head = head.with_addr(
-            head.addr().checked_sub(layout.size()).ok_or(AllocError)? & !(layout.align() - 1),
-        );
+        let unaligned = head.addr().checked_sub(size).ok_or(AllocError)?;
+        let new_head = head.with_addr(unaligned & !(align - 1));
 
         if new_head.addr() < self.lower.addr() {
             // oom
@@ -196,13 +517,36 @@ unsafe impl Allocator for Bump<'_> {
 
         self.head.set(new_head);
         self.count.set(self.count.get() + 1);
+        #[cfg(feature = "stats")]
+        self.padding
+            .set(self.padding.get() + (unaligned - new_head.addr()));
 
         Ok(NonNull::slice_from_raw_parts(
             unsafe { NonNull::new_unchecked(new_head) },
-            layout.size(),
+            size,
         ))
     }
 
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if old_layout.size() > 0 {
+            // the block served for `old_layout` may have room to spare
+            let size = self.rounded(old_layout.size()).ok_or(AllocError)?;
+            if new_layout.size() <= size && ptr.addr().get() & (new_layout.align() - 1) == 0 {
+                return Ok(NonNull::slice_from_raw_parts(ptr, size));
+            }
+        }
+
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.cast().as_ptr(), old_layout.size());
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+
     unsafe fn deallocate(&self, _: NonNull<u8>, layout: Layout) {
         if layout.size() > 0 {
             let count = self.count.get();
@@ -215,6 +559,33 @@ unsafe impl Allocator for Bump<'_> {
     }
 }
 
+// impl BumpHandle
+
+impl<'a> From<&'a Bump<'a>> for BumpHandle<'a> {
+    fn from(bump: &'a Bump<'a>) -> Self {
+        Self { bump }
+    }
+}
+
+unsafe impl Allocator for BumpHandle<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.bump.allocate(layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.bump.grow(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.bump.deallocate(ptr, layout)
+    }
+}
+
 // impl AtomicBump
 
 unsafe impl Sync for AtomicBump<'_> {}
@@ -225,6 +596,23 @@ impl<'a> AtomicBump<'a> {
         unsafe { Self::from_ptr(buf.as_mut_ptr(), buf.len()) }
     }
 
+    /// Creates a new atomic bump allocator which aligns every allocation to
+    /// at least `min_align` bytes.
+    ///
+    /// This is useful to stop allocations used by different cores sharing a
+    /// cache line, at the cost of padding between small allocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_align` is not a power of two.
+    pub fn with_min_align(buf: &'a mut [u8], min_align: usize) -> Self {
+        assert!(min_align.is_power_of_two());
+        Self {
+            min_align,
+            ..Self::new(buf)
+        }
+    }
+
     /// How many allocations has this allocator created?
     ///
     /// Once all buffers served by the allocator are deallocated the
@@ -233,6 +621,21 @@ impl<'a> AtomicBump<'a> {
     pub fn count(&self) -> usize {
         self.count.load(Relaxed)
     }
+
+    /// The `[lower, upper)` address range managed by this allocator.
+    #[inline]
+    pub fn bounds(&self) -> (*const u8, *const u8) {
+        (self.lower, self.upper)
+    }
+
+    /// Does `ptr` lie within the buffer managed by this allocator?
+    ///
+    /// This only checks `ptr` against [`bounds`](Self::bounds); it does not
+    /// track whether the allocation is still live.
+    #[inline]
+    pub fn owns(&self, ptr: *const u8) -> bool {
+        (self.lower.addr()..self.upper.addr()).contains(&ptr.addr())
+    }
 }
 
 impl AtomicBump<'_> {
@@ -254,9 +657,64 @@ impl AtomicBump<'_> {
             upper,
             head: AtomicPtr::new(upper),
             count: AtomicUsize::new(0),
+            min_align: 1,
             _marker: PhantomData,
         }
     }
+
+    /// Attempts to allocate a block of memory, giving up after
+    /// `max_retries` failed compare-and-swap attempts.
+    ///
+    /// Under heavy contention [`Allocator::allocate`] retries until it
+    /// succeeds; this puts a ceiling on the time spent allocating. A
+    /// [`AllocError`] is returned on exhausting the retries, in which case
+    /// the caller may back off and try again.
+    pub fn try_allocate_bounded(
+        &self,
+        layout: Layout,
+        max_retries: usize,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(NonNull::slice_from_raw_parts(NonNull::dangling(), 0));
+        }
+
+        let align = layout.align().max(self.min_align);
+        let mut head = self.head.load(Relaxed);
+        let mut retries = 0;
+
+        let new_head = loop {
+            // oom
+            let new_head = self
+                .bump_down(head, layout.size(), align)
+                .ok_or(AllocError)?;
+
+            match self.head.compare_exchange(head, new_head, Acquire, Relaxed) {
+                Ok(_) => break new_head,
+                Err(_) if retries == max_retries => return Err(AllocError),
+                Err(actual) => {
+                    head = actual;
+                    retries += 1;
+                }
+            }
+        };
+
+        self.count.fetch_add(1, Relaxed);
+
+        Ok(NonNull::slice_from_raw_parts(
+            unsafe { NonNull::new_unchecked(new_head) },
+            layout.size(),
+        ))
+    }
+
+    /// The new head after allocating `size` bytes aligned to `align` below
+    /// `head`, if it fits in the buffer.
+    #[inline]
+    fn bump_down(&self, head: *mut u8, size: usize, align: usize) -> Option<*mut u8> {
+        head.addr()
+            .checked_sub(size)
+            .map(|unaligned| head.with_addr(unaligned & !(align - 1)))
+            .filter(|new_head| new_head.addr() >= self.lower.addr())
+    }
 }
 
 unsafe impl Allocator for AtomicBump<'_> {
@@ -265,17 +723,13 @@ unsafe impl Allocator for AtomicBump<'_> {
             return Ok(NonNull::slice_from_raw_parts(NonNull::dangling(), 0));
         }
 
+        let align = layout.align().max(self.min_align);
         let mut ptr = MaybeUninit::uninit();
 
         if self
             .head
             .fetch_update(Acquire, Relaxed, |head| {
-                match head
-                    .addr()
-                    .checked_sub(layout.size())
-                    .map(|unaligned| head.with_addr(unaligned & !(layout.align() - 1)))
-                    .filter(|new_head| new_head.addr() >= self.lower.addr())
-                {
+                match self.bump_down(head, layout.size(), align) {
                     // safety: `ptr` is a valid pointer in local scope
                     Some(addr) => unsafe {
                         ptr::write(ptr.as_mut_ptr(), addr);
@@ -308,6 +762,100 @@ unsafe impl Allocator for AtomicBump<'_> {
     }
 }
 
+// impl TrackedBump
+
+impl<'a, 't> TrackedBump<'a, 't> {
+    /// Creates a new tracked bump allocator backed by a given buffer.
+    ///
+    /// `table` holds the addresses of live allocations; its length is the
+    /// number of allocations which can be tracked at once.
+    pub fn new(buf: &'a mut [u8], table: &'t mut [usize]) -> Self {
+        table.fill(0);
+        Self {
+            bump: Bump::new(buf),
+            live: Cell::from_mut(table).as_slice_of_cells(),
+            untracked: Cell::new(0),
+        }
+    }
+
+    /// How many allocations has this allocator created?
+    ///
+    /// Once all buffers served by the allocator are deallocated the
+    /// count will return to 0.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.bump.count()
+    }
+}
+
+unsafe impl Allocator for TrackedBump<'_, '_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.bump.allocate(layout)?;
+
+        if layout.size() > 0 {
+            match self.live.iter().find(|addr| addr.get() == 0) {
+                Some(addr) => addr.set(ptr.addr().get()),
+                None => self.untracked.set(self.untracked.get() + 1),
+            }
+        }
+
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() > 0 {
+            match self.live.iter().find(|addr| addr.get() == ptr.addr().get()) {
+                Some(addr) => addr.set(0),
+                None if self.untracked.get() > 0 => {
+                    self.untracked.set(self.untracked.get() - 1);
+                }
+                None => panic!(
+                    "[double free] :: {:p} is not a live allocation",
+                    ptr.as_ptr()
+                ),
+            }
+        }
+
+        self.bump.deallocate(ptr, layout);
+    }
+}
+
+// impl ZeroizeBump
+
+impl<'a> ZeroizeBump<'a> {
+    /// Creates a new zeroizing bump allocator backed by a given buffer.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            bump: Bump::new(buf),
+        }
+    }
+
+    /// How many allocations has this allocator created?
+    ///
+    /// Once all buffers served by the allocator are deallocated the
+    /// count will return to 0.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.bump.count()
+    }
+}
+
+unsafe impl Allocator for ZeroizeBump<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.bump.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        for i in 0..layout.size() {
+            // volatile so the wipe of a dead region is not optimized away
+            ptr::write_volatile(ptr.as_ptr().add(i), 0);
+        }
+        atomic::compiler_fence(SeqCst);
+
+        self.bump.deallocate(ptr, layout);
+    }
+}
+
 // impl GlobalBump
 
 unsafe impl<const N: usize> Sync for GlobalBump<N> {}
@@ -4,7 +4,7 @@ use std::mem;
 use std::sync::Barrier;
 use std::thread;
 
-use qbump::{static_buf, AtomicBump, Bump};
+use qbump::{static_buf, AtomicBump, Bump, BumpHandle, TrackedBump, ZeroizeBump};
 
 macro_rules! aligned_buf {
     ($len:literal, $align:literal) => {{
@@ -44,7 +44,7 @@ fn bump_zst() {
 fn bump_align_1() {
     let mut buf = aligned_buf!(1, 1);
     let bump = Bump::new(&mut buf);
-    let ptr = Box::into_raw(Box::try_new_in(0_u8, &bump).unwrap());
+    let ptr = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
     assert_eq!(ptr.align_offset(mem::align_of::<u8>()), 0);
 }
 
@@ -52,8 +52,8 @@ fn bump_align_1() {
 fn bump_align_2() {
     let mut buf = aligned_buf!(4, 2);
     let bump = Bump::new(&mut buf);
-    let _ = Box::into_raw(Box::try_new_in(0_u8, &bump).unwrap());
-    let ptr = Box::into_raw(Box::try_new_in(0_u16, &bump).unwrap());
+    let _ = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
+    let ptr = Box::into_raw_with_allocator(Box::try_new_in(0_u16, &bump).unwrap()).0;
     assert_eq!(ptr.align_offset(mem::align_of::<u16>()), 0);
 }
 
@@ -61,8 +61,8 @@ fn bump_align_2() {
 fn bump_align_4() {
     let mut buf = aligned_buf!(8, 4);
     let bump = Bump::new(&mut buf);
-    let _ = Box::into_raw(Box::try_new_in(0_u8, &bump).unwrap());
-    let ptr = Box::into_raw(Box::try_new_in(0_u32, &bump).unwrap());
+    let _ = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
+    let ptr = Box::into_raw_with_allocator(Box::try_new_in(0_u32, &bump).unwrap()).0;
     assert_eq!(ptr.align_offset(mem::align_of::<u32>()), 0);
 }
 
@@ -70,8 +70,8 @@ fn bump_align_4() {
 fn bump_align_8() {
     let mut buf = aligned_buf!(16, 8);
     let bump = Bump::new(&mut buf);
-    let _ = Box::into_raw(Box::try_new_in(0_u8, &bump).unwrap());
-    let ptr = Box::into_raw(Box::try_new_in(0_u64, &bump).unwrap());
+    let _ = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
+    let ptr = Box::into_raw_with_allocator(Box::try_new_in(0_u64, &bump).unwrap()).0;
     assert_eq!(ptr.align_offset(mem::align_of::<u64>()), 0);
 }
 
@@ -79,11 +79,49 @@ fn bump_align_8() {
 fn bump_align_16() {
     let mut buf = aligned_buf!(32, 16);
     let bump = Bump::new(&mut buf);
-    let _ = Box::into_raw(Box::try_new_in(0_u8, &bump).unwrap());
-    let ptr = Box::into_raw(Box::try_new_in(0_u128, &bump).unwrap());
+    let _ = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
+    let ptr = Box::into_raw_with_allocator(Box::try_new_in(0_u128, &bump).unwrap()).0;
     assert_eq!(ptr.align_offset(mem::align_of::<u128>()), 0);
 }
 
+#[test]
+fn bump_min_align() {
+    let mut buf = aligned_buf!(256, 64);
+    let bump = Bump::with_min_align(&mut buf, 64);
+    let a = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
+    let b = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
+    assert_eq!(a.align_offset(64), 0);
+    assert_eq!(b.align_offset(64), 0);
+    assert_eq!(a as usize - b as usize, 64);
+}
+
+#[test]
+fn bump_min_align_oom() {
+    let mut buf = aligned_buf!(128, 64);
+    let bump = Bump::with_min_align(&mut buf, 64);
+    let _a = Box::try_new_in(0_u8, &bump).unwrap();
+    let _b = Box::try_new_in(0_u8, &bump).unwrap();
+    assert!(Box::try_new_in(0_u8, &bump).is_err());
+}
+
+#[test]
+#[should_panic]
+fn bump_min_align_not_power_of_two() {
+    let mut buf = aligned_buf!(128, 64);
+    let _bump = Bump::with_min_align(&mut buf, 48);
+}
+
+#[test]
+fn atomic_bump_min_align() {
+    let mut buf = aligned_buf!(256, 64);
+    let bump = AtomicBump::with_min_align(&mut buf, 64);
+    let a = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
+    let b = Box::into_raw_with_allocator(Box::try_new_in(0_u8, &bump).unwrap()).0;
+    assert_eq!(a.align_offset(64), 0);
+    assert_eq!(b.align_offset(64), 0);
+    assert_eq!(a as usize - b as usize, 64);
+}
+
 #[test]
 fn bump_drop_one() {
     let mut buf = aligned_buf!(4, 4);
@@ -123,6 +161,289 @@ fn bump_dyn() {
     assert_eq!(v.v(), 123);
 }
 
+#[test]
+fn bump_bounds() {
+    let mut buf = aligned_buf!(8, 4);
+    let range = buf.as_ptr_range();
+    let bump = Bump::new(&mut buf);
+    assert_eq!(bump.bounds(), (range.start, range.end));
+    let ptr = Box::into_raw_with_allocator(Box::try_new_in(0_u32, &bump).unwrap()).0;
+    let (lower, upper) = bump.bounds();
+    assert!(lower <= ptr as *const u8 && (ptr as *const u8) < upper);
+}
+
+#[test]
+fn atomic_bump_bounds() {
+    let mut buf = aligned_buf!(8, 4);
+    let range = buf.as_ptr_range();
+    let bump = AtomicBump::new(&mut buf);
+    assert_eq!(bump.bounds(), (range.start, range.end));
+}
+
+#[test]
+fn bump_owns() {
+    let mut buf = aligned_buf!(8, 4);
+    let range = buf.as_ptr_range();
+    let bump = Bump::new(&mut buf);
+    let x = Box::try_new_in(0_u32, &bump).unwrap();
+    let local = 0_u32;
+
+    assert!(bump.owns(&*x as *const u32 as *const u8));
+    assert!(bump.owns(range.start));
+    assert!(!bump.owns(range.end));
+    assert!(!bump.owns(&local as *const u32 as *const u8));
+}
+
+#[test]
+fn atomic_bump_owns() {
+    let mut buf = aligned_buf!(8, 4);
+    let range = buf.as_ptr_range();
+    let bump = AtomicBump::new(&mut buf);
+    let x = Box::try_new_in(0_u32, &bump).unwrap();
+
+    assert!(bump.owns(&*x as *const u32 as *const u8));
+    assert!(!bump.owns(range.end));
+    assert!(!bump.owns(core::ptr::null()));
+}
+
+#[test]
+fn tracked_bump_alloc_dealloc() {
+    let mut buf = aligned_buf!(16, 4);
+    let mut table = [0; 2];
+    let bump = TrackedBump::new(&mut buf, &mut table);
+    let a = Box::try_new_in(1_u32, &bump).unwrap();
+    let b = Box::try_new_in(2_u32, &bump).unwrap();
+    let c = Box::try_new_in(3_u32, &bump).unwrap();
+    assert_eq!(bump.count(), 3);
+    drop(b);
+    drop(a);
+    drop(c);
+    assert_eq!(bump.count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "double free")]
+fn tracked_bump_double_free() {
+    use std::alloc::{Allocator, Layout};
+
+    let mut buf = aligned_buf!(8, 4);
+    let mut table = [0; 2];
+    let bump = TrackedBump::new(&mut buf, &mut table);
+    let layout = Layout::new::<u32>();
+    let a = bump.allocate(layout).unwrap().cast::<u8>();
+    let _b = bump.allocate(layout).unwrap();
+    unsafe {
+        bump.deallocate(a, layout);
+        bump.deallocate(a, layout);
+    }
+}
+
+#[test]
+fn zeroize_bump_wipes_on_deallocate() {
+    let mut buf = aligned_buf!(8, 4);
+    let bump = ZeroizeBump::new(&mut buf);
+    let a = Box::try_new_in(0xdead_beef_u32, &bump).unwrap();
+    let b = Box::try_new_in(0xcafe_f00d_u32, &bump).unwrap();
+    drop(a);
+    drop(b);
+    assert_eq!(bump.count(), 0);
+    assert_eq!(*buf, [0; 8]);
+}
+
+#[test]
+fn bump_slab_ascending_addresses() {
+    let mut buf = aligned_buf!(32, 4);
+    let bump = Bump::new(&mut buf);
+    let slab = bump.slab::<u32>(4).unwrap();
+    assert_eq!(*slab, [0; 4]);
+    for pair in slab.windows(2) {
+        assert!((&pair[0] as *const u32) < (&pair[1] as *const u32));
+    }
+    assert_eq!(bump.count(), 1);
+    assert!(bump.slab::<u32>(5).is_none());
+    drop(slab);
+    assert_eq!(bump.count(), 0);
+}
+
+#[test]
+fn bump_slab_zero_sized() {
+    let mut buf = aligned_buf!(4, 4);
+    let bump = Bump::new(&mut buf);
+    let slab = bump.slab::<u32>(0).unwrap();
+    assert!(slab.is_empty());
+    assert_eq!(bump.count(), 0);
+}
+
+#[test]
+fn bump_rebind() {
+    let mut small = aligned_buf!(4, 4);
+    let mut large = aligned_buf!(8, 4);
+    let mut bump = Bump::new(&mut small);
+    assert!(bump.slab::<u32>(2).is_none());
+    bump.rebind(&mut large);
+    let slab = bump.slab::<u32>(2).unwrap();
+    let (lower, upper) = bump.bounds();
+    assert_eq!(upper as usize - lower as usize, 8);
+    drop(slab);
+}
+
+#[test]
+#[should_panic]
+fn bump_rebind_live_allocations() {
+    let mut buf = aligned_buf!(4, 4);
+    let mut other = aligned_buf!(4, 4);
+    let mut bump = Bump::new(&mut buf);
+    mem::forget(Box::try_new_in(0_u32, &bump).unwrap());
+    bump.rebind(&mut other);
+}
+
+#[test]
+fn bump_mut_ref_allocator() {
+    fn alloc_in<A: std::alloc::Allocator>(alloc: A) -> usize {
+        let b = Box::try_new_in(7_u32, alloc).unwrap();
+        *b as usize
+    }
+
+    let mut buf = aligned_buf!(4, 4);
+    let mut bump = Bump::new(&mut buf);
+    assert_eq!(alloc_in(&mut bump), 7);
+    assert_eq!(bump.count(), 0);
+}
+
+#[test]
+fn bump_compact() {
+    let mut buf = aligned_buf!(8, 4);
+    let mut bump = Bump::new(&mut buf);
+    let b = Box::try_new_in(1_u32, &bump).unwrap();
+    mem::forget(Box::try_new_in(2_u32, &bump).unwrap());
+    drop(b);
+    assert_eq!(bump.count(), 1);
+    assert!(Box::try_new_in(3_u32, &bump).is_err());
+
+    bump.compact();
+    assert_eq!(bump.count(), 0);
+    let b = Box::try_new_in([4_u32; 2], &bump).unwrap();
+    assert_eq!(*b, [4, 4]);
+}
+
+#[test]
+fn bump_split() {
+    let mut buf = aligned_buf!(16, 4);
+    let [a, b, c] = Bump::split(&mut buf, [8, 4, 0]).unwrap();
+    let (a_lower, a_upper) = a.bounds();
+    let (b_lower, b_upper) = b.bounds();
+    assert_eq!(a_upper as usize - a_lower as usize, 8);
+    assert_eq!(b_lower, a_upper);
+    assert_eq!(b_upper as usize - b_lower as usize, 4);
+
+    let x = Box::try_new_in([1_u32; 2], &a).unwrap();
+    assert!(Box::try_new_in(0_u32, &a).is_err());
+    let y = Box::try_new_in(2_u32, &b).unwrap();
+    assert!(Box::try_new_in(0_u32, &c).is_err());
+    assert_eq!((*x, *y), ([1, 1], 2));
+    assert_eq!((a.count(), b.count(), c.count()), (1, 1, 0));
+}
+
+#[test]
+fn bump_split_too_large() {
+    let mut buf = aligned_buf!(16, 4);
+    assert!(Bump::split(&mut buf, [8, 9]).is_none());
+    assert!(Bump::split(&mut buf, [usize::MAX, 1]).is_none());
+    assert!(Bump::split(&mut buf, [16]).is_some());
+}
+
+#[test]
+fn bump_alloc_uninit() {
+    let mut buf = aligned_buf!(16, 8);
+    let bump = Bump::new(&mut buf);
+    let a = bump.alloc_uninit::<u8>().unwrap();
+    let b = bump.alloc_uninit::<u64>().unwrap();
+    assert_eq!(b.as_ptr() as usize % 8, 0);
+    a.write(1);
+    b.write(2);
+    assert_eq!(unsafe { (a.assume_init(), b.assume_init()) }, (1, 2));
+    assert!(bump.alloc_uninit::<u64>().is_none());
+    assert_eq!(bump.count(), 2);
+}
+
+#[test]
+fn bump_alloc_uninit_slice() {
+    let mut buf = aligned_buf!(16, 4);
+    let bump = Bump::new(&mut buf);
+    let s = bump.alloc_uninit_slice::<u32>(3).unwrap();
+    assert_eq!(s.len(), 3);
+    for (i, x) in s.iter_mut().enumerate() {
+        x.write(i as u32);
+    }
+    assert_eq!(unsafe { s[2].assume_init() }, 2);
+    assert!(bump.alloc_uninit_slice::<u32>(2).is_none());
+    assert_eq!(bump.alloc_uninit_slice::<u32>(0).unwrap().len(), 0);
+    assert_eq!(bump.alloc_uninit_slice::<()>(5).unwrap().len(), 5);
+    assert_eq!(bump.count(), 1);
+}
+
+#[test]
+fn bump_handle() {
+    struct Owner<'a> {
+        alloc: BumpHandle<'a>,
+    }
+
+    let mut buf = aligned_buf!(16, 8);
+    let bump = Bump::new(&mut buf);
+    let (a, b) = (
+        Owner {
+            alloc: bump.handle(),
+        },
+        Owner {
+            alloc: bump.handle(),
+        },
+    );
+
+    let x = Box::try_new_in(1_u64, a.alloc).unwrap();
+    let y = Box::try_new_in([2_u32, 3], b.alloc).unwrap();
+    assert_eq!(bump.count(), 2);
+    assert!(Box::try_new_in(4_u8, a.alloc).is_err());
+
+    drop((x, y));
+    assert_eq!(bump.count(), 0);
+    let _ = Box::try_new_in([0_u8; 16], BumpHandle::from(&bump)).unwrap();
+}
+
+#[test]
+fn bump_remaining_slab() {
+    let mut buf = aligned_buf!(32, 8);
+    let bump = Bump::new(&mut buf);
+    let _ = bump.alloc_uninit::<u8>().unwrap();
+    // 7 bytes of padding are skipped to align the slab
+    let s = bump.remaining_slab::<u64>().unwrap();
+    assert_eq!(s.len(), 3);
+    assert_eq!(s.as_ptr() as usize % 8, 0);
+    assert!(bump.remaining_slab::<u64>().is_none());
+    assert!(bump.remaining_slab::<()>().is_none());
+    assert_eq!(bump.count(), 2);
+
+    let mut buf = aligned_buf!(32, 8);
+    let bump = Bump::with_granularity(&mut buf, 8);
+    let _ = bump.alloc_uninit::<u8>().unwrap();
+    assert_eq!(bump.remaining_slab::<[u8; 3]>().unwrap().len(), 8);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn bump_padding_bytes() {
+    let mut buf = aligned_buf!(16, 8);
+    let bump = Bump::new(&mut buf);
+    let a = Box::try_new_in(1_u8, &bump).unwrap();
+    assert_eq!(bump.padding_bytes(), 0);
+    let b = Box::try_new_in(2_u64, &bump).unwrap();
+    assert_eq!(bump.padding_bytes(), 7);
+    drop((a, b));
+
+    // the running total survives the buffer being reused
+    let _c = Box::try_new_in(3_u64, &bump).unwrap();
+    assert_eq!(bump.padding_bytes(), 7);
+}
+
 #[test]
 fn static_bump() {
     let bump = Bump::new(static_buf!([u8; 8]));
@@ -130,6 +451,52 @@ fn static_bump() {
     assert_eq!(*ptr, 123);
 }
 
+#[test]
+fn atomic_bump_try_allocate_bounded() {
+    use std::alloc::{Allocator, Layout};
+
+    let mut buf = aligned_buf!(8, 4);
+    let bump = AtomicBump::new(&mut buf);
+    let layout = Layout::new::<u32>();
+    let a = bump.try_allocate_bounded(layout, 0).unwrap();
+    let b = bump.try_allocate_bounded(layout, 0).unwrap();
+    assert!(b.cast::<u8>() < a.cast::<u8>());
+    assert!(bump.try_allocate_bounded(layout, 8).is_err());
+    assert_eq!(bump.count(), 2);
+    unsafe {
+        bump.deallocate(a.cast(), layout);
+        bump.deallocate(b.cast(), layout);
+    }
+    assert_eq!(bump.count(), 0);
+}
+
+#[test]
+fn concurrent_atomic_bump_try_allocate_bounded() {
+    use std::alloc::{Allocator, Layout};
+
+    #[cfg(not(miri))]
+    const N: usize = 1024;
+    #[cfg(miri)]
+    const N: usize = 32;
+
+    let bump = AtomicBump::new(static_buf!([u8; 256]));
+    let layout = Layout::new::<u32>();
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..N {
+                    if let Ok(ptr) = bump.try_allocate_bounded(layout, 4) {
+                        unsafe { bump.deallocate(ptr.cast(), layout) };
+                    }
+                }
+            });
+        }
+    });
+
+    assert_eq!(bump.count(), 0);
+}
+
 #[test]
 fn concurrent_atomic_bump() {
     #[cfg(not(miri))]
@@ -170,3 +537,23 @@ fn concurrent_atomic_bump() {
         });
     })
 }
+
+#[test]
+fn bump_granularity() {
+    let mut buf = aligned_buf!(64, 8);
+    let bump = Bump::with_granularity(&mut buf, 16);
+
+    let mut vec = Vec::<u8, _>::new_in(&bump);
+    vec.try_reserve_exact(4).unwrap();
+    let ptr = vec.as_ptr();
+    vec.extend_from_slice(&[1, 2, 3, 4]);
+    vec.try_reserve_exact(12).unwrap();
+    assert_eq!(vec.as_ptr(), ptr);
+    vec.try_reserve_exact(13).unwrap();
+    assert_ne!(vec.as_ptr(), ptr);
+    assert_eq!(vec, [1, 2, 3, 4]);
+    assert_eq!(bump.count(), 1);
+
+    let (_, upper) = bump.bounds();
+    assert_eq!(upper as usize - vec.as_ptr() as usize, 16 + 32);
+}
@@ -22,6 +22,26 @@ pub fn bench_qcell(c: &mut Criterion) {
     });
 }
 
+pub fn bench_qcell_8_readers(c: &mut Criterion) {
+    c.bench_function("qcell (8 readers)", |b| {
+        b.iter(|| {
+            let cell = black_box(DoubleBufferedCell::new(0));
+            thread::scope(|s| {
+                for _ in 0..8 {
+                    s.spawn(|| while black_box(cell.read()) != 1024 {});
+                }
+                s.spawn(|| {
+                    for i in 0..=1024 {
+                        unsafe {
+                            cell.write_uncontended(&i);
+                        }
+                    }
+                });
+            });
+        });
+    });
+}
+
 pub fn bench_std_mutex(c: &mut Criterion) {
     c.bench_function("mutex (std)", |b| {
         b.iter(|| {
@@ -77,6 +97,7 @@ pub fn bench_flume(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_qcell,
+    bench_qcell_8_readers,
     bench_flume,
     bench_std_mutex,
     bench_parking_lot_mutex
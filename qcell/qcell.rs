@@ -37,14 +37,17 @@
 //! # fn task_logic(_: &State) {}
 //! use qcell::DoubleBufferedCell;
 //!
+//! # #[cfg(not(feature = "single-threaded"))]
 //! static CELL: DoubleBufferedCell<State> = DoubleBufferedCell::new(State::empty());
 //!
+//! # #[cfg(not(feature = "single-threaded"))]
 //! unsafe extern "C" fn isr() {
 //!     // the interrupt is the only writer of the state - it can write to the
 //!     // cell at the same time we are reading from it elsewhere
 //!     CELL.write_uncontended(&State::record_from_isr());
 //! }
 //!
+//! # #[cfg(not(feature = "single-threaded"))]
 //! fn task(_: *mut ()) -> ! {
 //!     loop {
 //!         // if we get interrupted during the read, we'll end up with the
@@ -53,18 +56,106 @@
 //!     }    
 //! }
 //! ```
+//!
+//! # Read Locks
+//!
+//! Readers take a shared lock on the published slot while they copy it, so
+//! the copy is a plain typed read and `T` may be any `Copy` type, padding
+//! included. Taking and releasing the lock is one read-modify-write of the
+//! cell flags each; the writer never waits on it, but always writes to the
+//! other slot.
+//!
+//! # Multiple Writers
+//!
+//! The single writer contract can be lifted with [`MpmcCell`], which spreads
+//! the data over more than two slots. Writers claim a free slot with CAS and
+//! publish it once written, and readers copy from the latest published slot.
+//! Both writes and reads are lock-free rather than wait-free: they may retry
+//! under contention.
+//!
+//! # Atomic Vocabulary
+//!
+//! Every cell also offers `load` and `store`, mirroring the types in
+//! [`core::sync::atomic`]. `load` is [`DoubleBufferedCell::read`] by another
+//! name. `store` takes its argument by value and forwards to the cell's
+//! write: for the single writer cells it is `unsafe` with the same contract
+//! as [`DoubleBufferedCell::write_uncontended`]. There are no memory
+//! orderings to pick; loads always observe the most recent completed store,
+//! as if by `Acquire`/`Release`.
+//!
+//! ```
+//! use qcell::DoubleBufferedCell;
+//!
+//! let cell = DoubleBufferedCell::new(0_u32);
+//!
+//! // safety: this is the only writer
+//! unsafe { cell.store(1) };
+//! assert_eq!(cell.load(), 1);
+//! ```
+//!
+//! # Async Streams
+//!
+//! With the `async` feature enabled, `DoubleBufferedCell::stream` turns a cell fed by
+//! an ISR into a stream of updates for an async executor (e.g., `embassy`).
+//! Each write wakes the task waiting on the stream, which then yields the
+//! new value unless it equals the value last yielded. A cell has room for
+//! one waiting task; a second stream polled on the same cell takes over the
+//! wake-ups from the first. The feature adds no dependencies; streams offer
+//! an inherent `poll_next` rather than implementing `futures::Stream`.
+//!
+//! # Targets Without Atomics
+//!
+//! On targets without native compare-and-swap, enable either the
+//! `atomic-polyfill` feature (emulated atomics), or the `critical-section`
+//! feature to update the cell flags inside a [`critical_section`] instead.
+//! The latter is often cheaper on single-core microcontrollers where a
+//! critical section simply disables interrupts.
+//!
+//! Without either feature the crate only builds for targets with native
+//! pointer sized atomics (`cfg(target_has_atomic = "ptr")`). Atomics which
+//! are emulated behind the scenes may take a lock, which an ISR can deadlock
+//! on, so choosing an emulation must be explicit.
+//!
+//! As a last resort for strictly single threaded programs, the
+//! `single-threaded` feature backs the cell flags with plain [`Cell`]s
+//! instead of atomics. The API is unchanged, but the cells are no longer
+//! `Sync`, so they cannot be placed in a `static` or shared between
+//! threads. **The cells are not ISR safe in this configuration**: an
+//! interrupt which preempts a read or write will see torn flags. This
+//! feature takes precedence over the others.
+//!
+//! [`critical_section`]: https://docs.rs/critical-section
+//! [`Cell`]: core::cell::Cell
 
 #![no_std]
 
+#[cfg(not(any(
+    target_has_atomic = "ptr",
+    feature = "atomic-polyfill",
+    feature = "critical-section",
+    feature = "single-threaded",
+)))]
+compile_error!(
+    "qcell requires native pointer sized atomics on this target; \
+     enable the `atomic-polyfill` or `critical-section` feature to opt in to emulation"
+);
+
 use core::cell::UnsafeCell;
 use core::hint;
 use core::mem::MaybeUninit;
 use core::ptr;
+#[cfg(feature = "async")]
+use core::task::{Context, Poll, Waker};
+
+#[cfg(all(feature = "atomic-polyfill", not(feature = "single-threaded")))]
+use atomic_polyfill::{AtomicUsize, Ordering, Ordering::*};
+#[cfg(not(any(feature = "atomic-polyfill", feature = "single-threaded")))]
+use core::sync::atomic::{AtomicUsize, Ordering, Ordering::*};
 
-#[cfg(feature = "atomic_polyfill")]
-use atomic_polyfill::{AtomicUsize, Ordering::*};
-#[cfg(not(feature = "atomic_polyfill"))]
-use core::sync::atomic::{AtomicUsize, Ordering::*};
+#[cfg(feature = "single-threaded")]
+use self::unsync::AtomicUsize;
+#[cfg(feature = "single-threaded")]
+use core::sync::atomic::{Ordering, Ordering::*};
 
 use self::{bits::*, Slot::*};
 
@@ -72,8 +163,72 @@ use self::{bits::*, Slot::*};
 pub struct DoubleBufferedCell<T> {
     flags: AtomicUsize,
     slots: [UnsafeCell<T>; 2],
+    /// The number of completed writes, for streams.
+    #[cfg(feature = "async")]
+    writes: AtomicUsize,
+    #[cfg(feature = "async")]
+    waker: WakerSlot,
+}
+
+/// A stream of the values written to a [`DoubleBufferedCell`].
+///
+/// Created with [`DoubleBufferedCell::stream`]. The stream never ends; it yields a
+/// value each time the cell is written to, skipping values equal to the one
+/// it last yielded.
+///
+/// ```
+/// use qcell::DoubleBufferedCell;
+///
+/// async fn task(cell: &DoubleBufferedCell<u32>) {
+///     let mut updates = cell.stream();
+///     while let Some(value) = updates.next().await {
+///         // react to `value`
+/// #       let _ = value;
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub struct CellStream<'a, T> {
+    cell: &'a DoubleBufferedCell<T>,
+    generation: usize,
+    last: Option<T>,
+}
+
+/// Storage for the waker of the task waiting on a cell.
+///
+/// Registering and waking may race (e.g., a write from an ISR while a task
+/// polls); the state flags make sure the waker is never accessed by both at
+/// once and that a wake during registration is not lost.
+#[cfg(feature = "async")]
+struct WakerSlot {
+    state: AtomicUsize,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+#[cfg(feature = "async")]
+const WAKER_REGISTERING: usize = 0x1;
+#[cfg(feature = "async")]
+const WAKER_WAKING: usize = 0x2;
+
+/// A synchronised cell for concurrent task communication with multiple
+/// writers.
+///
+/// Writers claim a free slot out of `SLOTS`, write to it and then publish
+/// it; readers copy from the most recently published slot. `SLOTS` must be
+/// at least 3; with more concurrent readers and writers than slots, tasks
+/// may spin waiting for a slot to become free.
+///
+/// For more information please consult the crate level documentation.
+pub struct MpmcCell<T, const SLOTS: usize> {
+    latest: AtomicUsize,
+    states: [AtomicUsize; SLOTS],
+    slots: UnsafeCell<[T; SLOTS]>,
 }
 
+/// Set in the state of an [`MpmcCell`] slot while a writer owns it; the
+/// remaining bits are the number of readers.
+const MPMC_WRITING: usize = !(usize::MAX >> 1);
+
 #[rustfmt::skip]
 mod bits {
     // writer flags
@@ -125,23 +280,171 @@ mod bits {
     pub const R2P2: usize    = R2 | P2;
 }
 
-#[derive(Debug, Copy, Clone)]
+/// One of the two buffer slots backing a [`DoubleBufferedCell`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(usize)]
-enum Slot {
+pub enum Slot {
+    /// The first buffer slot.
     Slot1,
+    /// The second buffer slot.
     Slot2,
 }
 
+/// Stand-ins for the atomics used by the cells, for single threaded
+/// programs on targets without atomics.
+///
+/// Orderings are ignored; there is no other thread to order against.
+#[cfg(feature = "single-threaded")]
+mod unsync {
+    use core::cell::Cell;
+    use core::sync::atomic::Ordering;
+
+    pub struct AtomicUsize(Cell<usize>);
+
+    impl AtomicUsize {
+        pub const fn new(v: usize) -> Self {
+            Self(Cell::new(v))
+        }
+
+        #[inline(always)]
+        pub fn load(&self, _: Ordering) -> usize {
+            self.0.get()
+        }
+
+        #[inline(always)]
+        pub fn store(&self, v: usize, _: Ordering) {
+            self.0.set(v)
+        }
+
+        #[cfg(not(feature = "critical-section"))]
+        #[inline(always)]
+        pub fn fetch_update<F>(&self, _: Ordering, _: Ordering, mut f: F) -> Result<usize, usize>
+        where
+            F: FnMut(usize) -> Option<usize>,
+        {
+            let v = self.0.get();
+            match f(v) {
+                Some(v_new) => {
+                    self.0.set(v_new);
+                    Ok(v)
+                }
+                None => Err(v),
+            }
+        }
+    }
+}
+
+/// Read-modify-write an atomic word.
+///
+/// With the `critical-section` feature enabled the update is performed
+/// inside a critical section rather than with a CAS loop.
+#[inline(always)]
+fn fetch_update<F>(a: &AtomicUsize, set: Ordering, fetch: Ordering, f: F) -> Result<usize, usize>
+where
+    F: FnMut(usize) -> Option<usize>,
+{
+    #[cfg(not(feature = "critical-section"))]
+    return a.fetch_update(set, fetch, f);
+
+    #[cfg(feature = "critical-section")]
+    {
+        // the critical section makes the update atomic, but some stores are
+        // published outside of one, so the orderings are still needed to
+        // synchronise with them
+        let _ = (set, fetch);
+        let mut f = f;
+        critical_section::with(|_| {
+            let b = a.load(Acquire);
+            match f(b) {
+                Some(b_new) => {
+                    a.store(b_new, Release);
+                    Ok(b)
+                }
+                None => Err(b),
+            }
+        })
+    }
+}
+
+/// A read lock on one slot of a [`DoubleBufferedCell`].
+///
+/// The lock is released when the guard is dropped.
+struct ReadGuard<'a, T> {
+    cell: &'a DoubleBufferedCell<T>,
+    slot: Slot,
+}
+
 // impl DoubleBufferedCell
 
+impl<T> DoubleBufferedCell<T> {
+    /// Read-modify-write the cell flags.
+    ///
+    /// With the `critical-section` feature enabled the update is performed
+    /// inside a critical section rather than with a CAS loop.
+    #[inline(always)]
+    fn update_flags<F>(&self, set: Ordering, fetch: Ordering, f: F) -> Result<usize, usize>
+    where
+        F: FnMut(usize) -> Option<usize>,
+    {
+        fetch_update(&self.flags, set, fetch, f)
+    }
+}
+
+#[cfg(not(feature = "single-threaded"))]
 unsafe impl<T: Copy + Send> Sync for DoubleBufferedCell<T> {}
 
 impl<T: Copy> DoubleBufferedCell<T> {
     /// Creates a new cell with an initial value.
     pub const fn new(init: T) -> Self {
+        Self::new_with_priority(init, Slot2)
+    }
+
+    /// Creates an array of cells which share an initial value.
+    ///
+    /// This is `const`, so can be used to declare a bank of cells in a
+    /// `static`:
+    ///
+    /// ```
+    /// use qcell::DoubleBufferedCell;
+    ///
+    /// # #[cfg(not(feature = "single-threaded"))]
+    /// static CHANNELS: [DoubleBufferedCell<u32>; 16] = DoubleBufferedCell::array(0);
+    ///
+    /// # #[cfg(not(feature = "single-threaded"))]
+    /// assert_eq!(CHANNELS[15].read(), 0);
+    /// ```
+    pub const fn array<const N: usize>(init: T) -> [Self; N] {
+        // safety: an array of `MaybeUninit` does not require initialization
+        let mut cells: [MaybeUninit<Self>; N] = unsafe { MaybeUninit::uninit().assume_init() };
+        let mut i = 0;
+        while i < N {
+            cells[i] = MaybeUninit::new(Self::new(init));
+            i += 1;
+        }
+
+        // safety: every cell is initialized, and `MaybeUninit<Self>` has the
+        // same layout as `Self`
+        unsafe { ptr::addr_of!(cells).cast::<[Self; N]>().read() }
+    }
+
+    /// Creates a new cell with an initial value and read priority.
+    ///
+    /// Until the first write, reads are served from the `start` slot; the
+    /// first write then goes to the other slot. [`DoubleBufferedCell::new`]
+    /// starts with [`Slot::Slot2`].
+    pub const fn new_with_priority(init: T, start: Slot) -> Self {
+        let priority = match start {
+            Slot1 => P1,
+            Slot2 => P2,
+        };
+
         Self {
-            flags: AtomicUsize::new(P2),
+            flags: AtomicUsize::new(priority),
             slots: [UnsafeCell::new(init), UnsafeCell::new(init)],
+            #[cfg(feature = "async")]
+            writes: AtomicUsize::new(0),
+            #[cfg(feature = "async")]
+            waker: WakerSlot::new(),
         }
     }
 
@@ -151,12 +454,77 @@ impl<T: Copy> DoubleBufferedCell<T> {
     /// there are a large number of concurrent readers. This ensures that
     /// barraging the cell with read operations does not cause it to get
     /// stuck in a state where it only reads stale data.
+    ///
+    /// The same nesting caveats as [`DoubleBufferedCell::read_with`] apply.
     pub fn read(&self) -> T {
+        let guard = self.lock_read();
+        // safety: api guarantees we have (possibly shared) read lock on pointer
+        unsafe { ptr::read_volatile(guard.get()) }
+    }
+
+    /// Reads the most recent value written to the cell, along with a lower
+    /// bound on the number of writes which completed before it (wrapping).
+    #[cfg(feature = "async")]
+    fn read_generation(&self) -> (T, usize) {
+        let generation = self.writes.load(Acquire);
+        (self.read(), generation)
+    }
+
+    /// Borrows the most recent value written to the cell.
+    ///
+    /// The read lock on the slot is held for the duration of `f`, so the
+    /// value does not need to be copied out of the cell. This is useful for
+    /// inspecting a few fields of a large payload.
+    ///
+    /// Writers are not blocked while `f` runs, but a long-running closure
+    /// will cause other readers to see stale data until it returns.
+    ///
+    /// This function _might_ sit in a CAS busy-loop for short periods if
+    /// there are a large number of concurrent borrows. This ensures that
+    /// barraging the cell with borrows does not cause it to get stuck in a
+    /// state where it only lends out stale data.
+    ///
+    /// Borrows must share a slot, so a borrow made while the same thread
+    /// already holds one (i.e., inside `f`, or from an ISR which preempted
+    /// it) sees the value held by the outer borrow; after a second write it
+    /// would wait on the outer borrow forever. Never call this function
+    /// while already borrowing from the cell.
+    pub fn read_with<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let guard = self.lock_read();
+        // safety: api guarantees we have (possibly shared) read lock on pointer
+        f(unsafe { &*guard.get() })
+    }
+
+    /// Reads the most recent value written to the cell.
+    ///
+    /// An alias for [`DoubleBufferedCell::read`].
+    #[inline(always)]
+    pub fn load(&self) -> T {
+        self.read()
+    }
+
+    /// Creates a stream of the values written to the cell from now on.
+    ///
+    /// The value held by the cell when the stream is created is not
+    /// yielded.
+    #[cfg(feature = "async")]
+    pub fn stream(&self) -> CellStream<'_, T> {
+        CellStream {
+            cell: self,
+            generation: self.read_generation().1,
+            last: None,
+        }
+    }
+
+    /// Acquire a (possibly shared) read lock on the most recent slot.
+    fn lock_read(&self) -> ReadGuard<'_, T> {
         let mut slot = MaybeUninit::uninit();
 
         while self
-            .flags
-            .fetch_update(Acquire, Relaxed, |mut b| {
+            .update_flags(Acquire, Relaxed, |mut b| {
                 debug_assert_ne!(
                     b & RMASK,
                     RMASK,
@@ -214,20 +582,8 @@ impl<T: Copy> DoubleBufferedCell<T> {
 
         // safety: we've initialized `slot` if we've left the spin-loop
         let slot = unsafe { slot.assume_init() };
-        // safety: `slot` as a `usize` can only be either 0 or 1
-        let cell = unsafe { self.slots.get_unchecked(slot as usize) };
-        // safety: api guarantees we have (possibly shared) read lock on pointer
-        let val = unsafe { ptr::read_volatile(cell.get()) };
 
-        let _ = self.flags.fetch_update(Release, Relaxed, |mut b| {
-            let num_rdrs = (b & RCMASK) >> RCSH;
-            if num_rdrs == 1 {
-                b &= !((slot as usize + 1) << RSH);
-            }
-            Some((b & !RCMASK) | (num_rdrs - 1) << RCSH)
-        });
-
-        val
+        ReadGuard { cell: self, slot }
     }
 
     /// Writes a value to the cell without waiting.
@@ -249,7 +605,7 @@ impl<T: Copy> DoubleBufferedCell<T> {
     pub unsafe fn write_uncontended(&self, value: &T) {
         let mut slot = MaybeUninit::uninit();
 
-        let _ = self.flags.fetch_update(Acquire, Relaxed, |b| {
+        let _ = self.update_flags(Acquire, Relaxed, |b| {
             debug_assert_eq!(
                 b & WMASK,
                 0,
@@ -281,12 +637,318 @@ impl<T: Copy> DoubleBufferedCell<T> {
         // safety: api guarantees we have write lock on pointer
         ptr::write_volatile(cell.get(), *value);
 
-        let _ = self.flags.fetch_update(Release, Relaxed, |mut b| {
+        let _ = self.update_flags(Release, Relaxed, |mut b| {
             debug_assert_eq!(b & WMASK, slot as usize + 1);
             b &= !((slot as usize + 1) << WSH);
             b &= !PMASK;
             b |= (slot as usize + 1) << PSH;
             Some(b)
         });
+        #[cfg(feature = "async")]
+        {
+            // there is only one writer, so no read-modify-write is needed
+            let writes = self.writes.load(Relaxed);
+            self.writes.store(writes.wrapping_add(1), Release);
+            self.waker.wake();
+        }
+    }
+
+    /// Writes a value to the cell without waiting.
+    ///
+    /// An alias for [`DoubleBufferedCell::write_uncontended`].
+    ///
+    /// # Safety
+    ///
+    /// The same contract as [`DoubleBufferedCell::write_uncontended`]
+    /// applies.
+    #[inline(always)]
+    pub unsafe fn store(&self, value: T) {
+        self.write_uncontended(&value);
+    }
+
+    /// Writes a value to the cell only if the most recently written value
+    /// equals `expected`, returning whether the write happened.
+    ///
+    /// # Safety
+    ///
+    /// The same contract as [`DoubleBufferedCell::write_uncontended`]
+    /// applies. As there is only one writer, the current value cannot change
+    /// between the comparison and the write.
+    pub unsafe fn write_if_current(&self, expected: &T, new: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        // only the writer moves the priority flags, so the published slot
+        // is stable; readers never write to it, so we can safely read it
+        let slot = if self.flags.load(Relaxed) & P1 != 0 {
+            Slot1
+        } else {
+            Slot2
+        };
+        // safety: `slot` as a `usize` can only be either 0 or 1
+        let cell = self.slots.get_unchecked(slot as usize);
+        let current = ptr::read_volatile(cell.get());
+
+        if current != *expected {
+            return false;
+        }
+
+        self.write_uncontended(new);
+        true
+    }
+}
+
+// impl ReadGuard
+
+impl<T> ReadGuard<'_, T> {
+    fn get(&self) -> *mut T {
+        // safety: `slot` as a `usize` can only be either 0 or 1
+        unsafe { self.cell.slots.get_unchecked(self.slot as usize).get() }
+    }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+    fn drop(&mut self) {
+        let slot = self.slot;
+        let _ = self.cell.update_flags(Release, Relaxed, |mut b| {
+            let num_rdrs = (b & RCMASK) >> RCSH;
+            debug_assert_ne!(num_rdrs, 0, "[bug] :: releasing a read with no readers");
+            if num_rdrs == 1 {
+                b &= !((slot as usize + 1) << RSH);
+            }
+            Some((b & !RCMASK) | (num_rdrs - 1) << RCSH)
+        });
+    }
+}
+
+// impl CellStream
+
+#[cfg(feature = "async")]
+impl<T: Copy + PartialEq> CellStream<'_, T> {
+    /// Polls for the next value written to the cell.
+    ///
+    /// Returns `Poll::Ready(Some(value))` once the cell has been written to
+    /// since the last value was yielded, and the value differs from it.
+    /// Otherwise the task is registered to be woken by the next write and
+    /// `Poll::Pending` is returned. The stream never returns `None`.
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        // register before reading, so a write after the read wakes the task
+        self.cell.waker.register(cx.waker());
+
+        let (val, generation) = self.cell.read_generation();
+        if generation == self.generation {
+            return Poll::Pending;
+        }
+        self.generation = generation;
+
+        if self.last == Some(val) {
+            return Poll::Pending;
+        }
+        self.last = Some(val);
+        Poll::Ready(Some(val))
+    }
+
+    /// Waits for the next value written to the cell.
+    ///
+    /// See [`CellStream::poll_next`].
+    pub async fn next(&mut self) -> Option<T> {
+        core::future::poll_fn(|cx| self.poll_next(cx)).await
+    }
+}
+
+// impl WakerSlot
+
+#[cfg(feature = "async")]
+impl WakerSlot {
+    const fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(0),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Stores the waker to be woken by the next call to `wake`.
+    ///
+    /// Only one task may register at a time (i.e., a stream must not be
+    /// polled concurrently with another stream on the same cell).
+    fn register(&self, waker: &Waker) {
+        if fetch_update(&self.state, Acquire, Acquire, |s| {
+            (s == 0).then_some(WAKER_REGISTERING)
+        })
+        .is_err()
+        {
+            // a wake is in progress, so poll again straight away
+            waker.wake_by_ref();
+            return;
+        }
+
+        // safety: the registering flag gives us exclusive access to the waker
+        unsafe {
+            let slot = &mut *self.waker.get();
+            if !slot.as_ref().is_some_and(|w| w.will_wake(waker)) {
+                *slot = Some(waker.clone());
+            }
+        }
+
+        let woken = fetch_update(&self.state, AcqRel, Acquire, |s| {
+            (s == WAKER_REGISTERING).then_some(0)
+        })
+        .is_err();
+
+        if woken {
+            // a wake arrived while registering; it could not take the waker,
+            // so wake it here instead
+            // safety: `wake` does not touch the waker while we are registering
+            let waker = unsafe { (*self.waker.get()).take() };
+            let _ = fetch_update(&self.state, AcqRel, Acquire, |_| Some(0));
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Wakes the registered task, if any.
+    fn wake(&self) {
+        let Ok(0) = fetch_update(&self.state, AcqRel, Acquire, |s| Some(s | WAKER_WAKING)) else {
+            // the task is registering, and will wake itself
+            return;
+        };
+
+        // safety: the waking flag gives us exclusive access to the waker
+        let waker = unsafe { (*self.waker.get()).take() };
+        let _ = fetch_update(&self.state, Release, Relaxed, |s| Some(s & !WAKER_WAKING));
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+// impl MpmcCell
+
+#[cfg(not(feature = "single-threaded"))]
+unsafe impl<T: Copy + Send, const SLOTS: usize> Sync for MpmcCell<T, SLOTS> {}
+
+impl<T: Copy, const SLOTS: usize> MpmcCell<T, SLOTS> {
+    /// Creates a new cell with an initial value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `SLOTS` is less than 3.
+    pub const fn new(init: T) -> Self {
+        assert!(SLOTS >= 3, "[mpmc] :: at least 3 slots are required");
+        Self {
+            latest: AtomicUsize::new(0),
+            states: [const { AtomicUsize::new(0) }; SLOTS],
+            slots: UnsafeCell::new([init; SLOTS]),
+        }
+    }
+
+    /// Reads the most recent value written to the cell.
+    ///
+    /// This function _might_ sit in a CAS busy-loop if the latest slot is
+    /// reclaimed by a writer before the read begins.
+    pub fn read(&self) -> T {
+        let slot = loop {
+            let slot = self.latest.load(Acquire);
+            // safety: `latest` is always a valid slot index
+            let state = unsafe { self.states.get_unchecked(slot) };
+
+            // every slot without a writer holds a complete, published value
+            if fetch_update(state, Acquire, Relaxed, |s| {
+                (s & MPMC_WRITING == 0).then_some(s + 1)
+            })
+            .is_ok()
+            {
+                break slot;
+            }
+
+            hint::spin_loop();
+        };
+
+        // safety: `slot` is a valid index and we hold a read lock on it
+        let val = unsafe { ptr::read_volatile(self.slot_ptr(slot)) };
+
+        // safety: `slot` is a valid index
+        let state = unsafe { self.states.get_unchecked(slot) };
+        let _ = fetch_update(state, Release, Relaxed, |s| Some(s - 1));
+
+        val
+    }
+
+    /// Writes a value to the cell.
+    ///
+    /// Unlike [`DoubleBufferedCell::write_uncontended`] any number of tasks
+    /// may write to the cell concurrently. This function _might_ sit in a
+    /// CAS busy-loop until a slot is free.
+    pub fn write(&self, value: &T) {
+        let slot = loop {
+            if let Some(slot) = self.claim_slot() {
+                break slot;
+            }
+            hint::spin_loop();
+        };
+
+        // safety: `slot` is a valid index and we hold the write lock on it
+        unsafe { ptr::write_volatile(self.slot_ptr(slot), *value) };
+
+        self.latest.store(slot, Release);
+        // safety: `slot` is a valid index
+        let state = unsafe { self.states.get_unchecked(slot) };
+        state.store(0, Release);
+    }
+
+    /// Reads the most recent value written to the cell.
+    ///
+    /// An alias for [`MpmcCell::read`].
+    #[inline(always)]
+    pub fn load(&self) -> T {
+        self.read()
+    }
+
+    /// Writes a value to the cell.
+    ///
+    /// An alias for [`MpmcCell::write`].
+    #[inline(always)]
+    pub fn store(&self, value: T) {
+        self.write(&value);
+    }
+
+    /// Attempt to claim the write lock on a slot which is neither being read
+    /// nor the latest published slot.
+    fn claim_slot(&self) -> Option<usize> {
+        let latest = self.latest.load(Relaxed);
+
+        for i in 1..SLOTS {
+            let slot = (latest + i) % SLOTS;
+            // safety: `slot` is a valid index
+            let state = unsafe { self.states.get_unchecked(slot) };
+
+            if fetch_update(state, Acquire, Relaxed, |s| {
+                (s == 0).then_some(MPMC_WRITING)
+            })
+            .is_err()
+            {
+                continue;
+            }
+
+            // only the owner of a slot can publish it, so once claimed the
+            // slot cannot become the latest behind our back
+            if self.latest.load(Acquire) == slot {
+                state.store(0, Release);
+                continue;
+            }
+
+            return Some(slot);
+        }
+
+        None
+    }
+
+    #[inline(always)]
+    fn slot_ptr(&self, slot: usize) -> *mut T {
+        debug_assert!(slot < SLOTS);
+        // safety: callers ensure `slot` is in bounds
+        unsafe { (self.slots.get() as *mut T).add(slot) }
     }
 }
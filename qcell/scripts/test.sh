@@ -4,6 +4,7 @@ set -e
 trap 'echo -e "\033[36m${BASH_COMMAND}\033[0m"' DEBUG
 
 cargo +stable test --verbose -p qcell -- --quiet
+cargo +stable test --verbose -p qcell --features critical-section -- --quiet
 cargo +nightly miri test --verbose -p qcell -- --quiet
 
 RUSTFLAGS="-Clink-args=-lc $RUSTFLAGS" cargo +stable run --verbose --profile nopanic -p qcell-nopanic --bin qcell_nopanic
@@ -1,9 +1,11 @@
+#![cfg(not(feature = "single-threaded"))]
+
 use std::panic;
 use std::sync::atomic::{AtomicBool, Ordering::*};
 use std::sync::Arc;
 use std::thread;
 
-use qcell::DoubleBufferedCell;
+use qcell::{DoubleBufferedCell, MpmcCell, Slot};
 
 #[cfg(miri)]
 const ITER: usize = 256;
@@ -123,3 +125,269 @@ fn write_uncontended_concurrent_readers() {
         });
     });
 }
+
+#[test]
+fn reader_count_boundary() {
+    const READERS: usize = 8;
+    #[cfg(miri)]
+    const ROUNDS: usize = 16;
+    #[cfg(not(miri))]
+    const ROUNDS: usize = 512;
+
+    let cell = DoubleBufferedCell::new(0_usize);
+    let exit = Exit::default();
+
+    thread::scope(|s| {
+        let readers = (0..READERS)
+            .map(|_| {
+                s.spawn(|| {
+                    // the count repeatedly climbs and drops back to zero
+                    for _ in 0..ROUNDS {
+                        cell.read_with(|_| thread::yield_now());
+                        cell.read();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        s.spawn(|| {
+            let mut i = 0;
+            while !exit.should_exit() {
+                i += 1;
+                unsafe { cell.write_uncontended(&i) };
+                thread::yield_now();
+            }
+        });
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        exit.exit();
+    });
+
+    // every reader has released its lock, so a write is immediately seen
+    unsafe { cell.write_uncontended(&usize::MAX) };
+    assert_eq!(cell.read(), usize::MAX);
+}
+
+#[test]
+fn new_with_priority() {
+    for start in [Slot::Slot1, Slot::Slot2] {
+        let cell = DoubleBufferedCell::new_with_priority(0_usize, start);
+        assert_eq!(cell.read(), 0);
+        for i in 1..=4 {
+            unsafe { cell.write_uncontended(&i) };
+            assert_eq!(cell.read(), i);
+        }
+    }
+}
+
+#[test]
+fn array() {
+    static CELLS: [DoubleBufferedCell<usize>; 4] = DoubleBufferedCell::array(7);
+    for (i, cell) in CELLS.iter().enumerate() {
+        assert_eq!(cell.read(), 7);
+        unsafe { cell.write_uncontended(&i) };
+    }
+    for (i, cell) in CELLS.iter().enumerate() {
+        assert_eq!(cell.read(), i);
+    }
+    assert_eq!(DoubleBufferedCell::<u8>::array::<0>(0).len(), 0);
+}
+
+#[test]
+fn single_thread_read_after_write() {
+    for start in [Slot::Slot1, Slot::Slot2] {
+        let cell = DoubleBufferedCell::new_with_priority(0_usize, start);
+        for i in 1..=8 {
+            unsafe { cell.write_uncontended(&i) };
+            assert_eq!(cell.read(), i);
+            assert_eq!(cell.read(), i);
+            assert_eq!(cell.read_with(|v| *v), i);
+        }
+        // consecutive writes without a read in between
+        for i in 9..=16 {
+            unsafe { cell.write_uncontended(&i) };
+        }
+        assert_eq!(cell.read(), 16);
+    }
+
+    let cell = MpmcCell::<_, 3>::new(0_usize);
+    for i in 1..=8 {
+        cell.write(&i);
+        assert_eq!(cell.read(), i);
+    }
+}
+
+#[test]
+fn read_within_read_with() {
+    let cell = DoubleBufferedCell::new(0_usize);
+    cell.read_with(|_| {
+        unsafe { cell.write_uncontended(&1) };
+        // the nested read shares the slot held by the outer read
+        assert_eq!(cell.read(), 0);
+    });
+    assert_eq!(cell.read(), 1);
+}
+
+#[test]
+fn read_with() {
+    let cell = DoubleBufferedCell::new([0_usize; 64]);
+    assert_eq!(cell.read_with(|v| v[63]), 0);
+    unsafe { cell.write_uncontended(&[1; 64]) };
+    assert_eq!(cell.read_with(|v| v[63]), 1);
+    // the read lock is released once the closure returns
+    unsafe { cell.write_uncontended(&[2; 64]) };
+    assert_eq!(cell.read_with(|v| v.iter().sum::<usize>()), 128);
+}
+
+#[test]
+fn write_uncontended_padded() {
+    // `a` is followed by padding, which must never be read as data
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    struct Padded {
+        a: u8,
+        b: u32,
+    }
+
+    let cell = DoubleBufferedCell::new(Padded { a: 0, b: 0 });
+
+    thread::scope(|s| {
+        s.spawn(|| unsafe {
+            for i in 0..=ITER {
+                let a = i as u8;
+                cell.write_uncontended(&Padded {
+                    a,
+                    b: u32::from(a) * 3,
+                });
+            }
+        });
+        for _ in 0..ITER {
+            let Padded { a, b } = cell.read();
+            assert_eq!(u32::from(a) * 3, b);
+            cell.read_with(|v| assert_eq!(u32::from(v.a) * 3, v.b));
+        }
+    });
+}
+
+#[test]
+fn read_with_concurrent_reads() {
+    let cell = DoubleBufferedCell::new(0_usize);
+    let exit = Exit::default();
+
+    thread::scope(|s| {
+        // holding borrows forces the writer onto the published slot
+        s.spawn(|| {
+            while !exit.should_exit() {
+                cell.read_with(|_| thread::yield_now());
+            }
+        });
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut prev = 0;
+                    while prev != ITER {
+                        let next = cell.read();
+                        assert!(next >= prev, "next={}, prev={}", next, prev);
+                        prev = next;
+                    }
+                })
+            })
+            .collect();
+        s.spawn(|| unsafe {
+            for i in 0..=ITER {
+                cell.write_uncontended(&i);
+            }
+        });
+
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        exit.exit();
+    });
+}
+
+#[test]
+fn write_if_current() {
+    for start in [Slot::Slot1, Slot::Slot2] {
+        let cell = DoubleBufferedCell::new_with_priority(0_usize, start);
+        unsafe {
+            assert!(!cell.write_if_current(&1, &2));
+            assert_eq!(cell.read(), 0);
+            assert!(cell.write_if_current(&0, &1));
+            assert_eq!(cell.read(), 1);
+            assert!(cell.write_if_current(&1, &2));
+            assert_eq!(cell.read(), 2);
+            assert!(!cell.write_if_current(&1, &3));
+            assert_eq!(cell.read(), 2);
+        }
+    }
+}
+
+#[test]
+fn mpmc_read_write() {
+    let cell = MpmcCell::<usize, 3>::new(0);
+    assert_eq!(cell.read(), 0);
+    for i in 1..=8 {
+        cell.write(&i);
+        assert_eq!(cell.read(), i);
+    }
+}
+
+#[test]
+fn mpmc_concurrent_writers_and_readers() {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    struct Dummy([usize; 8]);
+
+    let cell = MpmcCell::<Dummy, 8>::new(Dummy([0; 8]));
+    let exit = Exit::default();
+
+    thread::scope(|s| {
+        let readers = (0..4)
+            .map(|_| {
+                s.spawn(|| {
+                    while !exit.should_exit() {
+                        let Dummy(val) = cell.read();
+                        // writers only ever write uniform arrays
+                        assert!(val.iter().all(|v| *v == val[0]), "{:X?}", val);
+                        thread::yield_now();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let writers = (1..=4)
+            .map(|w| {
+                let cell = &cell;
+                s.spawn(move || {
+                    for i in 0..ITER / 16 {
+                        cell.write(&Dummy([w * ITER + i; 8]));
+                        thread::yield_now();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        exit.exit();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    });
+
+    let Dummy(val) = cell.read();
+    assert!(val.iter().all(|v| *v == val[0]));
+    assert_eq!(val[0] % ITER, ITER / 16 - 1);
+}
+
+#[test]
+fn load_store() {
+    let cell = DoubleBufferedCell::new(0_usize);
+    let mpmc = MpmcCell::<usize, 3>::new(0);
+    for i in 1..=4 {
+        unsafe { cell.store(i) };
+        mpmc.store(i);
+        assert_eq!((cell.load(), mpmc.load()), (i, i));
+        assert_eq!((cell.read(), mpmc.read()), (i, i));
+    }
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "async")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+use qcell::DoubleBufferedCell;
+
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn stream_yields_new_values() {
+    let cell = DoubleBufferedCell::new(0_u32);
+    let mut stream = cell.stream();
+    let mut cx = Context::from_waker(Waker::noop());
+
+    // the value held on creation is not yielded
+    assert_eq!(stream.poll_next(&mut cx), Poll::Pending);
+
+    unsafe { cell.store(1) };
+    assert_eq!(stream.poll_next(&mut cx), Poll::Ready(Some(1)));
+    assert_eq!(stream.poll_next(&mut cx), Poll::Pending);
+
+    // repeated values are skipped
+    unsafe { cell.store(1) };
+    assert_eq!(stream.poll_next(&mut cx), Poll::Pending);
+
+    // only the latest of several writes is seen
+    unsafe {
+        cell.store(2);
+        cell.store(3);
+    }
+    assert_eq!(stream.poll_next(&mut cx), Poll::Ready(Some(3)));
+}
+
+#[test]
+fn stream_wakes_on_write() {
+    let cell = DoubleBufferedCell::new(0_u32);
+    let mut stream = cell.stream();
+
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    // nothing is registered until the stream is polled
+    unsafe { cell.store(1) };
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+    assert_eq!(stream.poll_next(&mut cx), Poll::Ready(Some(1)));
+    assert_eq!(stream.poll_next(&mut cx), Poll::Pending);
+    unsafe { cell.store(2) };
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+    // a waker is woken once per registration
+    unsafe { cell.store(3) };
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert_eq!(stream.poll_next(&mut cx), Poll::Ready(Some(3)));
+}
+
+#[test]
+#[cfg(not(feature = "single-threaded"))]
+fn stream_across_threads() {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = pin!(fut);
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+            thread::park();
+        }
+    }
+
+    static CELL: DoubleBufferedCell<u32> = DoubleBufferedCell::new(0);
+    const LAST: u32 = 1000;
+
+    let mut stream = CELL.stream();
+    let writer = thread::spawn(|| {
+        for i in 1..=LAST {
+            unsafe { CELL.store(i) };
+        }
+    });
+
+    let mut prev = 0;
+    while prev != LAST {
+        let value = block_on(stream.next()).unwrap();
+        assert!(value > prev, "{value} after {prev}");
+        prev = value;
+    }
+
+    writer.join().unwrap();
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "single-threaded")]
+
+use qcell::{DoubleBufferedCell, MpmcCell};
+
+#[test]
+fn single_threaded_read_write() {
+    let cell = DoubleBufferedCell::new(0_usize);
+    let mpmc = MpmcCell::<usize, 3>::new(0);
+    for i in 1..=4 {
+        unsafe { cell.write_uncontended(&i) };
+        mpmc.write(&i);
+        assert_eq!((cell.read(), mpmc.read()), (i, i));
+        assert_eq!(cell.read_with(|v| *v), i);
+    }
+}
+
+#[test]
+fn single_threaded_thread_local() {
+    thread_local! {
+        static CELL: DoubleBufferedCell<u32> = const { DoubleBufferedCell::new(1) };
+    }
+
+    CELL.with(|cell| {
+        unsafe { cell.store(2) };
+        assert_eq!(cell.load(), 2);
+    });
+}
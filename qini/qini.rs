@@ -24,29 +24,38 @@
 //! # Usage
 //!
 //! Use [qini::parse] to iterate through key/value pairs in your .INI
-//! configuration file.
+//! configuration file, or [qini::bind] to parse values straight into typed
+//! fields. [qini::values] collects the values of a repeated key.
 //!
 //! # Grammar
 //!
-//! * Comments begin with `;` or `#` and must exist on their own line (inline
-//!   comments are not supported).
+//! * Comments begin with `;` or `#` and must exist on their own line, unless
+//!   inline comments are enabled with [Options::inline_comments].
 //! * Global key/value pairs can exist outside sections.
 //! * Values are delimited by the first `=` or `:` character encountered.
-//! * Multi-line values are not supported.
+//! * Multi-line values are not supported, other than indented continuation
+//!   lines with [qini::parse_continued].
 //! * Indentation is ignored.
 //! * Section and key names must contain only ASCII alphanumerics,
-//!   underscores, and periods.
+//!   underscores, and periods (and hyphens, with [Options::hyphens]).
 //! * Keys can have no value, but a valid delimiter must be present on the
 //!   line.
 //! * Duplicate sections and keys do not cause errors.
+//! * Lines may end with `\n` or `\r\n`.
 //!
 //! [ini.rs]: ../src/ini/ini.rs.html
 //! [qini::parse]: fn.parse.html
+//! [qini::bind]: fn.bind.html
+//! [qini::values]: fn.values.html
+//! [qini::parse_continued]: fn.parse_continued.html
+//! [Options::inline_comments]: struct.Options.html#structfield.inline_comments
+//! [Options::hyphens]: struct.Options.html#structfield.hyphens
 
 #![no_std]
 
-use core::iter::{Enumerate, Iterator};
-use core::str::Lines;
+use core::iter::{self, Enumerate, Iterator};
+use core::mem;
+use core::str::{self, Lines};
 
 use self::ErrorKind::*;
 
@@ -73,7 +82,7 @@ use self::ErrorKind::*;
 /// let mut config = Config::default();
 /// let mut iter = qini::parse(CONFIGURATION);
 ///
-/// while let Some(Ok(qini::Param { section, key, value })) = iter.next() {
+/// while let Some(Ok(qini::Param { section, key, value, .. })) = iter.next() {
 ///     match (section, key) {
 ///         ("", "description") => config.description = value,
 ///
@@ -101,11 +110,292 @@ use self::ErrorKind::*;
 /// assert_eq!(config.port, 53);
 /// ```
 pub fn parse(ini: &str) -> impl Iterator<Item = Result<Param<'_>, Error>> {
-    Parser::new(ini)
+    Parser::new(ini, Options::new(), 0)
+}
+
+/// Parses .INI configuration with the given parser options.
+///
+/// See [`parse`] for more information.
+///
+/// # Examples
+///
+/// ```
+/// let opts = qini::Options {
+///     inline_comments: true,
+///     ..qini::Options::new()
+/// };
+///
+/// let param = qini::parse_with("port = 53 ; dns", opts).next().unwrap().unwrap();
+///
+/// assert_eq!(param.value, "53");
+/// assert_eq!(param.raw_value, "53 ; dns");
+/// ```
+pub fn parse_with(ini: &str, opts: Options) -> impl Iterator<Item = Result<Param<'_>, Error>> {
+    Parser::new(ini, opts, 0)
+}
+
+/// Parses .INI configuration which is a fragment of a larger file.
+///
+/// Reported line numbers are offset by `base`, the number of lines in the
+/// enclosing file which precede the fragment. [`parse`] uses a `base` of 0.
+///
+/// # Examples
+///
+/// ```
+/// let err = qini::parse_with_base_line("[server]\n!port = 53", 10)
+///     .find_map(Result::err)
+///     .unwrap();
+///
+/// assert_eq!(err.lineno(), 12);
+/// ```
+pub fn parse_with_base_line(
+    ini: &str,
+    base: usize,
+) -> impl Iterator<Item = Result<Param<'_>, Error>> {
+    Parser::new(ini, Options::new(), base)
+}
+
+/// Parses .INI configuration where values may continue onto indented
+/// lines, as with Python's `configparser`.
+///
+/// A line which is indented and has no delimiter continues the value of the
+/// parameter before it; a blank line or comment ends the value. The lines of
+/// a continued value are trimmed and joined with `\n` into `scratch`, which
+/// must be large enough to hold every continued value in the file; otherwise
+/// [`ErrorKind::InsufficientBuffer`] is returned. Values on a single line are
+/// borrowed from `ini` as usual.
+///
+/// # Examples
+///
+/// ```
+/// const CONFIGURATION: &str = r#"
+/// [dns]
+/// servers = 8.8.8.8
+///     8.8.4.4
+/// port = 53
+/// "#;
+///
+/// let mut scratch = [0; 32];
+/// let mut iter = qini::parse_continued(CONFIGURATION, &mut scratch);
+///
+/// assert_eq!(iter.next().unwrap().unwrap().value, "8.8.8.8\n8.8.4.4");
+/// assert_eq!(iter.next().unwrap().unwrap().value, "53");
+/// assert!(iter.next().is_none());
+/// ```
+pub fn parse_continued<'a>(
+    ini: &'a str,
+    scratch: &'a mut [u8],
+) -> impl Iterator<Item = Result<Param<'a>, Error>> {
+    parse_continued_with(ini, scratch, Options::new())
+}
+
+/// Parses .INI configuration where values may continue onto indented
+/// lines, with the given parser options.
+///
+/// Options apply to continuation lines as well: with
+/// [`Options::inline_comments`] each line has its comment stripped before it
+/// is joined.
+///
+/// See [`parse_continued`] for more information.
+///
+/// # Examples
+///
+/// ```
+/// let opts = qini::Options {
+///     inline_comments: true,
+///     ..qini::Options::new()
+/// };
+///
+/// let mut scratch = [0; 32];
+/// let ini = "servers = 8.8.8.8 ; primary\n    8.8.4.4 ; secondary";
+/// let mut iter = qini::parse_continued_with(ini, &mut scratch, opts);
+///
+/// assert_eq!(iter.next().unwrap().unwrap().value, "8.8.8.8\n8.8.4.4");
+/// ```
+pub fn parse_continued_with<'a>(
+    ini: &'a str,
+    mut scratch: &'a mut [u8],
+    opts: Options,
+) -> impl Iterator<Item = Result<Param<'a>, Error>> {
+    let mut parser = Parser::new(ini, opts, 0);
+    iter::from_fn(move || {
+        Some(parser.next()?.and_then(|mut param| {
+            if let Some((value, last)) = parser.join_continued(param.value, &mut scratch)? {
+                // the raw value runs on to the end of the last line, which
+                // is borrowed from `ini` like the first
+                let start = param.raw_value.as_ptr() as usize - ini.as_ptr() as usize;
+                let end = last.as_ptr() as usize + last.len() - ini.as_ptr() as usize;
+                param.value = value;
+                param.raw_value = &ini[start..end];
+            }
+            Ok(param)
+        }))
+    })
+}
+
+/// Parses .INI configuration, binding values into typed targets.
+///
+/// Each entry in `targets` is a `(section, key, target)` triple. Every
+/// parameter matching a section and key is parsed into its target; later
+/// parameters overwrite earlier ones. Parameters without a matching entry
+/// are ignored.
+///
+/// Parsing stops at the first error, including a value which cannot be
+/// parsed as the type of its target ([`ErrorKind::InvalidValue`]).
+///
+/// # Examples
+///
+/// ```
+/// const CONFIGURATION: &str = r#"
+/// description = google's primary dns server
+///
+/// [server]
+/// port    = 53
+/// enabled = true
+/// "#;
+///
+/// let mut description = None;
+/// let mut port = None;
+/// let mut enabled = None;
+///
+/// qini::bind(
+///     CONFIGURATION,
+///     &mut [
+///         ("", "description", qini::Target::Str(&mut description)),
+///         ("server", "port", qini::Target::I64(&mut port)),
+///         ("server", "enabled", qini::Target::Bool(&mut enabled)),
+///     ],
+/// )
+/// .unwrap();
+///
+/// assert_eq!(description, Some("google's primary dns server"));
+/// assert_eq!(port, Some(53));
+/// assert_eq!(enabled, Some(true));
+/// ```
+pub fn bind<'a>(ini: &'a str, targets: &mut [(&str, &str, Target<'a, '_>)]) -> Result<(), Error> {
+    let mut parser = Parser::new(ini, Options::new(), 0);
+
+    while let Some(param) = parser.next() {
+        let param = param?;
+        let target = targets
+            .iter_mut()
+            .find(|(section, key, _)| *section == param.section && *key == param.key);
+
+        if let Some((_, _, target)) = target {
+            target.bind(param.value).map_err(|kind| Error {
+                lineno: parser.lineno,
+                kind,
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects every value of a repeated key into `out`.
+///
+/// Values of parameters matching `section` and `key` are written to `out`
+/// in the order they appear, and the number written is returned. Values
+/// which do not fit into `out` are skipped, but the rest of the input is
+/// still checked for errors.
+///
+/// # Examples
+///
+/// ```
+/// const CONFIGURATION: &str = r#"
+/// [dns]
+/// server = 8.8.8.8
+/// server = 8.8.4.4
+/// port   = 53
+/// server = 1.1.1.1
+/// "#;
+///
+/// let mut servers = [""; 4];
+/// let n = qini::values(CONFIGURATION, "dns", "server", &mut servers).unwrap();
+///
+/// assert_eq!(&servers[..n], ["8.8.8.8", "8.8.4.4", "1.1.1.1"]);
+/// ```
+pub fn values<'a>(
+    ini: &'a str,
+    section: &str,
+    key: &str,
+    out: &mut [&'a str],
+) -> Result<usize, Error> {
+    let mut n = 0;
+
+    for param in parse(ini) {
+        let param = param?;
+        if param.section == section && param.key == key {
+            if let Some(slot) = out.get_mut(n) {
+                *slot = param.value;
+                n += 1;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+/// Parses .INI configuration, yielding section headers alongside
+/// parameters.
+///
+/// Like [`parse`], but a [`Line::SectionHeader`] is yielded whenever a
+/// section is entered, so sections without any keys are observable.
+/// Parameters are yielded as [`Line::Param`]; blank lines and comments are
+/// skipped (see [`document`] to keep them).
+///
+/// # Examples
+///
+/// ```
+/// use qini::Line;
+///
+/// let mut iter = qini::parse_with_sections("[empty]\n[server]\nport = 53");
+///
+/// assert!(matches!(iter.next(), Some(Ok(Line::SectionHeader { name: "empty", .. }))));
+/// assert!(matches!(iter.next(), Some(Ok(Line::SectionHeader { name: "server", .. }))));
+/// assert!(matches!(iter.next(), Some(Ok(Line::Param { .. }))));
+/// assert!(iter.next().is_none());
+/// ```
+pub fn parse_with_sections(ini: &str) -> impl Iterator<Item = Result<Line<'_>, Error>> {
+    document(ini).filter(|line| !matches!(line, Ok(Line::Blank(_) | Line::Comment(_))))
+}
+
+/// Parses .INI configuration line by line.
+///
+/// Unlike [`parse`], blank lines, comments and section headers are yielded
+/// in order alongside parameters. Every line carries its original text
+/// (see [`Line::raw`]), so a file can be re-emitted with its comments and
+/// layout intact, rewriting only the lines which changed.
+///
+/// # Examples
+///
+/// ```
+/// use qini::Line;
+///
+/// let ini = "; comment\n\n[server]\nport = 53";
+/// let mut iter = qini::document(ini);
+///
+/// assert!(matches!(iter.next(), Some(Ok(Line::Comment("; comment")))));
+/// assert!(matches!(iter.next(), Some(Ok(Line::Blank("")))));
+/// assert!(matches!(iter.next(), Some(Ok(Line::SectionHeader { name: "server", .. }))));
+/// assert!(matches!(iter.next(), Some(Ok(Line::Param { raw: "port = 53", .. }))));
+/// assert!(iter.next().is_none());
+///
+/// let mut out = String::new();
+/// for line in qini::document(ini) {
+///     out.push_str(line.unwrap().raw());
+///     out.push('\n');
+/// }
+/// assert_eq!(out.trim_end(), ini);
+/// ```
+pub fn document(ini: &str) -> impl Iterator<Item = Result<Line<'_>, Error>> {
+    let mut parser = Parser::new(ini, Options::new(), 0);
+    iter::from_fn(move || parser.next_line())
 }
 
 /// .INI configuration parameter.
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct Param<'a> {
     /// The section the parameter was found in.
     ///
@@ -120,6 +410,82 @@ pub struct Param<'a> {
     ///
     /// Parameters with no value will have an empty string in this field.
     pub value: &'a str,
+
+    /// The text following the delimiter, including any inline comment.
+    ///
+    /// This is the same as `value` unless inline comments are enabled. A
+    /// value continued onto later lines (see [`parse_continued`]) has the
+    /// text of every line, up to the end of the last, as written.
+    pub raw_value: &'a str,
+}
+
+/// Parser options.
+///
+/// The default options only accept the grammar described in the crate level
+/// documentation.
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    /// Strip comments which follow a value or section header on the same
+    /// line.
+    ///
+    /// An inline comment begins with `;` or `#` preceded by whitespace (or
+    /// at the start of the value), e.g. `port = 53 ; dns`.
+    pub inline_comments: bool,
+
+    /// The maximum length of a line in bytes (excluding the line ending).
+    ///
+    /// Longer lines fail with [`ErrorKind::LineTooLong`] before any further
+    /// processing. This guards against pathological untrusted input. Lines
+    /// are unbounded by default.
+    pub max_line_len: Option<usize>,
+
+    /// Allow hyphens in section and key names, e.g. `max-connections`.
+    ///
+    /// A name may not start with a hyphen, so `-x` and `---` are still
+    /// rejected.
+    pub hyphens: bool,
+}
+
+/// A typed destination for a parameter value; see [`bind`].
+#[derive(Debug)]
+pub enum Target<'a, 'b> {
+    /// Binds the value as is.
+    Str(&'b mut Option<&'a str>),
+
+    /// Binds a signed integer value.
+    I64(&'b mut Option<i64>),
+
+    /// Binds `true` or `false`.
+    Bool(&'b mut Option<bool>),
+}
+
+/// A single line of .INI configuration.
+///
+/// Each line carries its original text, excluding the line ending.
+#[derive(Debug)]
+pub enum Line<'a> {
+    /// An empty or whitespace-only line; contains the original line.
+    Blank(&'a str),
+
+    /// A comment; contains the original line, including the comment
+    /// character and any indentation.
+    Comment(&'a str),
+
+    /// A section header.
+    SectionHeader {
+        /// The section name.
+        name: &'a str,
+        /// The original line.
+        raw: &'a str,
+    },
+
+    /// A key/value parameter.
+    Param {
+        /// The parsed parameter.
+        param: Param<'a>,
+        /// The original line.
+        raw: &'a str,
+    },
 }
 
 /// Error encountered while parsing .INI configuration files.
@@ -132,12 +498,25 @@ pub struct Error {
 /// Specific types of errors.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ErrorKind {
+    /// Scratch buffer is too small for a continued value (see
+    /// [`parse_continued`]), or for [`Param::canonical_key`].
+    InsufficientBuffer,
+
     /// Section contains invalid characters.
     InvalidSection,
 
     /// Key contains invalid characters.
     InvalidKey,
 
+    /// Value cannot be parsed as the type of its [`Target`].
+    InvalidValue,
+
+    /// Line is longer than [`Options::max_line_len`].
+    LineTooLong,
+
+    /// Text follows the closing `]` of a section header.
+    TrailingAfterSection,
+
     /// The parser reached the end of the line.
     UnexpectedEol,
 }
@@ -145,11 +524,121 @@ pub enum ErrorKind {
 struct Parser<'a> {
     lines: Enumerate<Lines<'a>>,
     section: &'a str,
+    base: usize,
+    lineno: usize,
+    opts: Options,
 }
 
-fn is_valid_ident(ident: &str) -> bool {
+fn is_valid_ident(ident: &str, hyphens: bool) -> bool {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.';
     !ident.is_empty()
-        && !ident.contains(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+        && !ident.starts_with('-')
+        && !ident.contains(|c: char| !(valid(c) || (hyphens && c == '-')))
+}
+
+/// Strip an inline comment from the end of `s`.
+fn strip_inline_comment(s: &str) -> &str {
+    let mut prev_ws = true;
+    for (i, c) in s.char_indices() {
+        if prev_ws && (c == ';' || c == '#') {
+            return &s[..i];
+        }
+        prev_ws = c.is_whitespace();
+    }
+    s
+}
+
+// impl Param
+
+impl Param<'_> {
+    /// Returns whether the parameter has a non-empty value.
+    ///
+    /// A key followed by a delimiter and nothing else (e.g. `key =`) is
+    /// valid, but has no value.
+    pub fn has_value(&self) -> bool {
+        !self.value.is_empty()
+    }
+
+    /// Returns whether the parameter is global, i.e. precedes every section
+    /// header.
+    ///
+    /// Global parameters have an empty `section`.
+    pub fn is_global(&self) -> bool {
+        self.section.is_empty()
+    }
+
+    /// Returns whether the parameter's section matches `section`, ignoring
+    /// ASCII case.
+    ///
+    /// Only ASCII letters are folded; other bytes must match exactly.
+    pub fn section_bytes_eq_ignore_ascii_case(&self, section: &str) -> bool {
+        self.section.eq_ignore_ascii_case(section)
+    }
+
+    /// Writes the ASCII-lowercased key into `buf`, returning it as a string
+    /// (e.g., to use as a case-insensitive map key without allocating).
+    ///
+    /// Fails with [`ErrorKind::InsufficientBuffer`] if `buf` is shorter than
+    /// the key.
+    ///
+    /// ```
+    /// let param = qini::parse("Port = 53").next().unwrap().unwrap();
+    ///
+    /// let mut buf = [0; 8];
+    /// assert_eq!(param.canonical_key(&mut buf), Ok("port"));
+    /// ```
+    pub fn canonical_key<'b>(&self, buf: &'b mut [u8]) -> Result<&'b str, ErrorKind> {
+        let buf = buf.get_mut(..self.key.len()).ok_or(InsufficientBuffer)?;
+        buf.copy_from_slice(self.key.as_bytes());
+        buf.make_ascii_lowercase();
+        Ok(str::from_utf8(buf).unwrap_or_default())
+    }
+}
+
+// impl Line
+
+impl<'a> Line<'a> {
+    /// Returns the original text of the line, excluding the line ending.
+    pub fn raw(&self) -> &'a str {
+        match *self {
+            Line::Blank(raw)
+            | Line::Comment(raw)
+            | Line::SectionHeader { raw, .. }
+            | Line::Param { raw, .. } => raw,
+        }
+    }
+}
+
+// impl Target
+
+impl<'a> Target<'a, '_> {
+    fn bind(&mut self, value: &'a str) -> Result<(), ErrorKind> {
+        match self {
+            Target::Str(out) => **out = Some(value),
+            Target::I64(out) => **out = Some(value.parse().map_err(|_| InvalidValue)?),
+            Target::Bool(out) => **out = Some(value.parse().map_err(|_| InvalidValue)?),
+        }
+        Ok(())
+    }
+}
+
+// impl Options
+
+impl Options {
+    /// Creates the default parser options.
+    pub const fn new() -> Self {
+        Self {
+            inline_comments: false,
+            max_line_len: None,
+            hyphens: false,
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // impl Error
@@ -169,22 +658,33 @@ impl Error {
 // impl Parser
 
 impl<'a> Parser<'a> {
-    fn new(src: &'a str) -> Self {
+    fn new(src: &'a str, opts: Options, base: usize) -> Self {
         Self {
             lines: src.lines().enumerate(),
             section: "",
+            base,
+            lineno: base,
+            opts,
         }
     }
 
-    fn parse_section(&mut self, section_start: &'a str) -> Result<(), ErrorKind> {
-        let section = section_start.strip_suffix(']').ok_or(UnexpectedEol)?.trim();
+    fn parse_section(&mut self, section_start: &'a str) -> Result<&'a str, ErrorKind> {
+        let (section, mut trailing) = section_start.split_once(']').ok_or(UnexpectedEol)?;
+        if self.opts.inline_comments {
+            trailing = strip_inline_comment(trailing);
+        }
+        if !trailing.trim().is_empty() {
+            return Err(TrailingAfterSection);
+        }
+
+        let section = section.trim();
 
-        if !is_valid_ident(section) {
+        if !is_valid_ident(section, self.opts.hyphens) {
             return Err(InvalidSection);
         }
 
         self.section = section;
-        Ok(())
+        Ok(section)
     }
 
     fn parse_param(&self, line: &'a str) -> Result<Param<'a>, ErrorKind> {
@@ -193,39 +693,149 @@ impl<'a> Parser<'a> {
         prefix = prefix.trim();
         suffix = suffix.trim();
 
-        if !is_valid_ident(prefix) {
+        if !is_valid_ident(prefix, self.opts.hyphens) {
             return Err(InvalidKey);
         }
 
+        let value = if self.opts.inline_comments {
+            strip_inline_comment(suffix).trim_end()
+        } else {
+            suffix
+        };
+
         Ok(Param {
             section: self.section,
             key: prefix,
-            value: suffix,
+            value,
+            raw_value: suffix,
         })
     }
 }
 
+impl<'a> Parser<'a> {
+    /// Takes the next line if it continues the value of a parameter.
+    ///
+    /// Returns the line with and without any inline comment.
+    fn next_continuation(&mut self) -> Result<Option<(&'a str, &'a str)>, Error> {
+        let Some((_, raw)) = self.lines.clone().next() else {
+            return Ok(None);
+        };
+        let mut line = raw.trim();
+        let continued = raw.starts_with([' ', '\t'])
+            && !line.is_empty()
+            && !line.starts_with([';', '#'])
+            && !line.contains(['=', ':']);
+
+        if !continued {
+            return Ok(None);
+        }
+
+        if let Some((lineno, _)) = self.lines.next() {
+            self.lineno = self.base + lineno + 1;
+        }
+        if self.opts.max_line_len.is_some_and(|max| raw.len() > max) {
+            return Err(Error {
+                lineno: self.lineno,
+                kind: LineTooLong,
+            });
+        }
+        let raw = line;
+        if self.opts.inline_comments {
+            line = strip_inline_comment(line).trim_end();
+        }
+
+        Ok(Some((line, raw)))
+    }
+
+    /// Joins `first` and any continuation lines after it into the front of
+    /// `scratch`, leaving the rest of `scratch` for later values.
+    ///
+    /// Returns `None` if there are no continuation lines, or the joined
+    /// value and the last line as written.
+    fn join_continued(
+        &mut self,
+        first: &str,
+        scratch: &mut &'a mut [u8],
+    ) -> Result<Option<(&'a str, &'a str)>, Error> {
+        let Some((mut line, mut raw)) = self.next_continuation()? else {
+            return Ok(None);
+        };
+
+        let buf = mem::take(scratch);
+        let mut len = 0;
+        let mut push = |s: &str| match buf.get_mut(len..len + s.len()) {
+            Some(dst) => {
+                dst.copy_from_slice(s.as_bytes());
+                len += s.len();
+                true
+            }
+            None => false,
+        };
+
+        let mut fits = push(first);
+        let mut overflow = None;
+        loop {
+            fits = fits && push("\n") && push(line);
+            if !fits && overflow.is_none() {
+                overflow = Some(self.lineno);
+            }
+            match self.next_continuation()? {
+                Some(next) => (line, raw) = next,
+                None => break,
+            }
+        }
+
+        if let Some(lineno) = overflow {
+            *scratch = buf;
+            return Err(Error {
+                lineno,
+                kind: InsufficientBuffer,
+            });
+        }
+
+        let (value, rest) = buf.split_at_mut(len);
+        *scratch = rest;
+        let value: &'a [u8] = value;
+        // only whole `str`s were copied into the buffer
+        Ok(Some((str::from_utf8(value).unwrap_or_default(), raw)))
+    }
+
+    fn next_line(&mut self) -> Option<Result<Line<'a>, Error>> {
+        let (lineno, raw) = self.lines.next()?;
+        let lineno = self.base + lineno + 1;
+        self.lineno = lineno;
+        let map_err = |kind| Error { lineno, kind };
+
+        if self.opts.max_line_len.is_some_and(|max| raw.len() > max) {
+            return Some(Err(map_err(LineTooLong)));
+        }
+
+        let line = raw.trim();
+
+        let parsed = match line.chars().next() {
+            None => Ok(Line::Blank(raw)),
+            Some('#') | Some(';') => Ok(Line::Comment(raw)),
+            Some('[') => self
+                .parse_section(&line[1..])
+                .map(|name| Line::SectionHeader { name, raw }),
+            Some(_) => self
+                .parse_param(line)
+                .map(|param| Line::Param { param, raw }),
+        };
+
+        Some(parsed.map_err(map_err))
+    }
+}
+
 impl<'a> Iterator for Parser<'a> {
     type Item = Result<Param<'a>, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let (lineno, mut line) = self.lines.next()?;
-            let map_err = |kind| Error {
-                lineno: lineno + 1,
-                kind,
-            };
-
-            line = line.trim();
-
-            if !matches!(line.chars().next(), Some('#') | Some(';') | None) {
-                if let Some(section_start) = line.strip_prefix('[') {
-                    if let Err(kind) = self.parse_section(section_start) {
-                        return Some(Err(map_err(kind)));
-                    }
-                } else {
-                    return Some(self.parse_param(line).map_err(map_err));
-                }
+            match self.next_line()? {
+                Ok(Line::Param { param, .. }) => return Some(Ok(param)),
+                Ok(_) => (),
+                Err(err) => return Some(Err(err)),
             }
         }
     }
@@ -206,9 +206,29 @@ test_err! {
     comment_after_section,
     "[foo] ; disallowed",
     1,
+    qini::ErrorKind::TrailingAfterSection,
+}
+
+test_err! {
+    text_after_section,
+    "\n[foo]bar",
+    2,
+    qini::ErrorKind::TrailingAfterSection,
+}
+
+test_err! {
+    unterminated_section,
+    "[foo",
+    1,
     qini::ErrorKind::UnexpectedEol,
 }
 
+test_ok! {
+    whitespace_after_section,
+    "[foo] \t\nbar = 1",
+    [("foo", "bar", "1")],
+}
+
 test_err! {
     invalid_symbol_in_section,
     "[bad section name]",
@@ -222,3 +242,346 @@ test_err! {
     1,
     qini::ErrorKind::InvalidKey,
 }
+
+#[test]
+fn has_value() {
+    let mut params = qini::parse("foo = 1\nbar =\nbaz =   ");
+    assert!(params.next().unwrap().unwrap().has_value());
+    assert!(!params.next().unwrap().unwrap().has_value());
+    assert!(!params.next().unwrap().unwrap().has_value());
+    assert!(params.next().is_none());
+}
+
+#[test]
+fn is_global() {
+    let mut params = qini::parse("foo = 1\n[server]\nbar = 2");
+    assert!(params.next().unwrap().unwrap().is_global());
+    assert!(!params.next().unwrap().unwrap().is_global());
+    assert!(params.next().is_none());
+}
+
+#[test]
+fn document() {
+    let ini = "; top\n\n  # indented\nfoo = 1\n[bar]\n\tbaz = 2\n";
+    let lines = qini::document(ini).collect::<Result<Vec<_>, _>>().unwrap();
+
+    assert_eq!(lines.len(), 6);
+    assert!(matches!(lines[0], qini::Line::Comment("; top")));
+    assert!(matches!(lines[1], qini::Line::Blank("")));
+    assert!(matches!(lines[2], qini::Line::Comment("  # indented")));
+    assert!(matches!(
+        lines[3],
+        qini::Line::Param {
+            param: qini::Param {
+                section: "",
+                key: "foo",
+                value: "1",
+                ..
+            },
+            raw: "foo = 1",
+        },
+    ));
+    assert!(matches!(
+        lines[4],
+        qini::Line::SectionHeader {
+            name: "bar",
+            raw: "[bar]"
+        }
+    ));
+    assert!(matches!(
+        lines[5],
+        qini::Line::Param {
+            param: qini::Param {
+                section: "bar",
+                key: "baz",
+                value: "2",
+                ..
+            },
+            raw: "\tbaz = 2",
+        },
+    ));
+}
+
+#[test]
+fn document_raw_roundtrip() {
+    let ini = "; top\n \t \n[ bar ]  \n  baz\t=  2   \n\nqux:\n";
+    let mut out = String::new();
+    for line in qini::document(ini) {
+        out.push_str(line.unwrap().raw());
+        out.push('\n');
+    }
+    assert_eq!(out, ini);
+
+    let mut lines = qini::document(ini).skip(1);
+    assert!(matches!(lines.next(), Some(Ok(qini::Line::Blank(" \t ")))));
+    assert!(matches!(
+        lines.next(),
+        Some(Ok(qini::Line::SectionHeader {
+            name: "bar",
+            raw: "[ bar ]  "
+        }))
+    ));
+}
+
+#[test]
+fn document_err() {
+    let mut lines = qini::document("foo = 1\n[bad section]\nbar = 2");
+    assert!(lines.next().unwrap().is_ok());
+    let err = lines.next().unwrap().unwrap_err();
+    assert_eq!(err.lineno(), 2);
+    assert_eq!(err.kind(), qini::ErrorKind::InvalidSection);
+    assert!(lines.next().unwrap().is_ok());
+}
+
+#[test]
+fn parse_with_base_line() {
+    let err = qini::parse_with_base_line("foo = 1\n\n[bad section]", 20)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    assert_eq!(err.lineno(), 23);
+    assert_eq!(err.kind(), qini::ErrorKind::InvalidSection);
+}
+
+#[test]
+fn inline_comments() {
+    let mut opts = qini::Options::new();
+    opts.inline_comments = true;
+
+    let ini = "[server] # primary\nip = 8.8.8.8 ; google\nurl = a#b\nempty = ; nothing\r\n";
+    let params = qini::parse_with(ini, opts)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(params.len(), 3);
+    assert_eq!(params[0].section, "server");
+    assert_eq!(params[0].value, "8.8.8.8");
+    assert_eq!(params[0].raw_value, "8.8.8.8 ; google");
+    assert_eq!(params[1].value, "a#b");
+    assert_eq!(params[1].raw_value, "a#b");
+    assert_eq!(params[2].value, "");
+    assert_eq!(params[2].raw_value, "; nothing");
+}
+
+#[test]
+fn inline_comments_disabled() {
+    let param = qini::parse("ip = 8.8.8.8 ; google")
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(param.value, "8.8.8.8 ; google");
+    assert_eq!(param.raw_value, param.value);
+}
+
+#[test]
+fn max_line_len() {
+    let mut opts = qini::Options::new();
+    opts.max_line_len = Some(8);
+
+    let mut params = qini::parse_with("foo = 12\nbar = 123\nbaz = 1", opts);
+    assert_eq!(params.next().unwrap().unwrap().value, "12");
+    let err = params.next().unwrap().unwrap_err();
+    assert_eq!(err.lineno(), 2);
+    assert_eq!(err.kind(), qini::ErrorKind::LineTooLong);
+    assert_eq!(params.next().unwrap().unwrap().value, "1");
+}
+
+#[test]
+fn hyphens() {
+    let ini = "[web-server]\nmax-connections = 10\nkey- = 1";
+
+    let err = qini::parse(ini).next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), qini::ErrorKind::InvalidSection);
+
+    let mut opts = qini::Options::new();
+    opts.hyphens = true;
+    let params = qini::parse_with(ini, opts)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(params[0].section, "web-server");
+    assert_eq!(params[0].key, "max-connections");
+    assert_eq!(params[1].key, "key-");
+
+    for (ini, kind) in [
+        ("-key = 1", qini::ErrorKind::InvalidKey),
+        ("--- = 1", qini::ErrorKind::InvalidKey),
+        ("[-section]", qini::ErrorKind::InvalidSection),
+    ] {
+        let err = qini::parse_with(ini, opts).next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), kind, "{ini}");
+    }
+}
+
+#[test]
+fn bind() {
+    let ini =
+        "name = a\n[server]\nport = 53\nport = 54\nenabled = false\nother = x\n[client]\nport = 1";
+    let mut name = None;
+    let mut port = None;
+    let mut enabled = None;
+    let mut missing = None;
+
+    qini::bind(
+        ini,
+        &mut [
+            ("", "name", qini::Target::Str(&mut name)),
+            ("server", "port", qini::Target::I64(&mut port)),
+            ("server", "enabled", qini::Target::Bool(&mut enabled)),
+            ("server", "missing", qini::Target::Str(&mut missing)),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(name, Some("a"));
+    assert_eq!(port, Some(54));
+    assert_eq!(enabled, Some(false));
+    assert_eq!(missing, None);
+}
+
+#[test]
+fn bind_invalid_value() {
+    let mut port = None;
+    let err = qini::bind(
+        "[server]\n\nport = dns",
+        &mut [("server", "port", qini::Target::I64(&mut port))],
+    )
+    .unwrap_err();
+    assert_eq!(err.lineno(), 3);
+    assert_eq!(err.kind(), qini::ErrorKind::InvalidValue);
+}
+
+#[test]
+fn parse_with_sections() {
+    let ini = "; comment\nglobal = 1\n\n[empty]\n[server]\nport = 53\n[bad section]";
+    let mut lines = qini::parse_with_sections(ini);
+
+    assert!(
+        matches!(lines.next(), Some(Ok(qini::Line::Param { param: p, .. })) if p.key == "global")
+    );
+    assert!(matches!(
+        lines.next(),
+        Some(Ok(qini::Line::SectionHeader { name: "empty", .. }))
+    ));
+    assert!(matches!(
+        lines.next(),
+        Some(Ok(qini::Line::SectionHeader { name: "server", .. }))
+    ));
+    assert!(matches!(
+        lines.next(),
+        Some(Ok(qini::Line::Param { param: p, .. })) if p.section == "server" && p.key == "port"
+    ));
+    let err = lines.next().unwrap().unwrap_err();
+    assert_eq!(err.lineno(), 7);
+    assert!(lines.next().is_none());
+}
+
+#[test]
+fn values() {
+    let ini = "host = x\n[dns]\nhost = a\nport = 53\nhost = b\n[other]\nhost = c\n[dns]\nhost = d";
+
+    let mut out = [""; 4];
+    assert_eq!(qini::values(ini, "dns", "host", &mut out).unwrap(), 3);
+    assert_eq!(out, ["a", "b", "d", ""]);
+
+    let mut out = [""; 2];
+    assert_eq!(qini::values(ini, "dns", "host", &mut out).unwrap(), 2);
+    assert_eq!(out, ["a", "b"]);
+
+    let mut out = [""; 1];
+    assert_eq!(qini::values(ini, "dns", "missing", &mut out).unwrap(), 0);
+    assert_eq!(qini::values(ini, "", "host", &mut out).unwrap(), 1);
+    assert_eq!(out, ["x"]);
+}
+
+#[test]
+fn values_err() {
+    let mut out = [""; 1];
+    let err = qini::values("[dns]\nhost = a\n!host = b", "dns", "host", &mut out).unwrap_err();
+    assert_eq!(err.lineno(), 3);
+}
+
+#[test]
+fn parse_continued() {
+    let ini = "a = 1\n  2\n\t3 \nb = x\n  ; comment\n  c = y\n[s]\nd =\n  z\n";
+    let mut scratch = [0; 16];
+    let params = qini::parse_continued(ini, &mut scratch)
+        .map(|p| p.map(|p| (p.section, p.key, p.value)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(
+        params,
+        [
+            ("", "a", "1\n2\n3"),
+            ("", "b", "x"),
+            ("", "c", "y"),
+            ("s", "d", "\nz"),
+        ]
+    );
+}
+
+#[test]
+fn parse_continued_insufficient_buffer() {
+    let ini = "a = 1\n  2\nb = 3\n  4\n  5";
+    let mut scratch = [0; 5];
+    let mut iter = qini::parse_continued(ini, &mut scratch);
+
+    assert_eq!(iter.next().unwrap().unwrap().value, "1\n2");
+    let err = iter.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), qini::ErrorKind::InsufficientBuffer);
+    assert_eq!(err.lineno(), 4);
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn parse_continued_with() {
+    let opts = qini::Options {
+        inline_comments: true,
+        hyphens: true,
+        ..qini::Options::new()
+    };
+    let ini = "a-b = 1 ; one\n  2 # two\n  3\nc = x";
+    let mut scratch = [0; 16];
+    let params = qini::parse_continued_with(ini, &mut scratch, opts)
+        .map(|p| p.map(|p| (p.key, p.value)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(params, [("a-b", "1\n2\n3"), ("c", "x")]);
+
+    let mut scratch = [0; 16];
+    let params = qini::parse_continued_with(ini, &mut scratch, opts)
+        .map(|p| p.map(|p| p.raw_value))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(params, ["1 ; one\n  2 # two\n  3", "x"]);
+
+    let opts = qini::Options {
+        max_line_len: Some(8),
+        ..qini::Options::new()
+    };
+    let mut scratch = [0; 16];
+    let mut iter = qini::parse_continued_with("a = 1\n  2\n  3456789", &mut scratch, opts);
+    let err = iter.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), qini::ErrorKind::LineTooLong);
+    assert_eq!(err.lineno(), 3);
+}
+
+#[test]
+fn case_insensitive_helpers() {
+    let param = qini::parse("[Server.DNS]\nPrimary_Addr = 8.8.8.8")
+        .next()
+        .unwrap()
+        .unwrap();
+
+    assert!(param.section_bytes_eq_ignore_ascii_case("server.dns"));
+    assert!(!param.section_bytes_eq_ignore_ascii_case("server"));
+
+    let mut buf = [0; 16];
+    assert_eq!(param.canonical_key(&mut buf), Ok("primary_addr"));
+    let mut buf = [0; 11];
+    assert_eq!(
+        param.canonical_key(&mut buf),
+        Err(qini::ErrorKind::InsufficientBuffer)
+    );
+}
@@ -1,5 +1,4 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use qjson::Schema as QSchema;
 
 const DATA: &str = r#"{
     "employees": [
@@ -51,26 +50,24 @@ pub fn bench_qjson(c: &mut Criterion) {
                     }],
             } = &mut es;
 
-            let mut e0_phones_desc = [QSchema::Str(e0_phone0), QSchema::Str(e0_phone1)];
-            let mut e0_desc = [
-                ("name", QSchema::Str(e0_name)),
-                ("id", QSchema::Integer(e0_id)),
-                ("phones", QSchema::Array(&mut e0_phones_desc)),
-                ("bonus", QSchema::Float(e0_bonus)),
-            ];
+            let mut desc = qjson::schema!({
+                "employees": [
+                    {
+                        "name": Str(e0_name),
+                        "id": Integer(e0_id),
+                        "phones": [Str(e0_phone0), Str(e0_phone1)],
+                        "bonus": Float(e0_bonus),
+                    },
+                    {
+                        "name": Str(e1_name),
+                        "id": Integer(e1_id),
+                        "phones": [Str(e1_phone0), Str(e1_phone1)],
+                        "bonus": Float(e1_bonus),
+                    },
+                ],
+            });
 
-            let mut e1_phones_desc = [QSchema::Str(e1_phone0), QSchema::Str(e1_phone1)];
-            let mut e1_desc = [
-                ("name", QSchema::Str(e1_name)),
-                ("id", QSchema::Integer(e1_id)),
-                ("phones", QSchema::Array(&mut e1_phones_desc)),
-                ("bonus", QSchema::Float(e1_bonus)),
-            ];
-
-            let mut es_desc = [QSchema::Object(&mut e0_desc), QSchema::Object(&mut e1_desc)];
-            let mut desc = [("employees", QSchema::Array(&mut es_desc))];
-
-            black_box(qjson::from_str::<_, 2>(black_box(DATA), &mut desc)).unwrap();
+            black_box(qjson::from_str::<_, 4>(black_box(DATA), &mut desc)).unwrap();
         });
     });
 }
@@ -40,7 +40,11 @@
 
 #![no_std]
 
-use core::str::Chars;
+use core::fmt;
+use core::iter;
+use core::marker::PhantomData;
+use core::mem;
+use core::str::{self, Chars, FromStr};
 
 #[cfg(not(debug_assertions))]
 use core::hint;
@@ -58,6 +62,11 @@ use self::{ErrorKind::*, Token::*};
 /// with an error. Use this to maintain strict control over how much stack
 /// the parser consumes.
 ///
+/// `D` is the deepest nesting of objects and arrays: `{"a":{}}` and
+/// `{"a":[1]}` both need a `D` of 2, and `[[[1]]]` needs a `D` of 3. Clear
+/// [`Options::count_arrays`] to only count objects, leaving the nesting of
+/// arrays unbounded.
+///
 /// # Example
 ///
 /// ```
@@ -95,7 +104,7 @@ use self::{ErrorKind::*, Token::*};
 /// ];
 ///
 /// // Specify the max recursion depth at the call site
-/// qjson::from_str::<_, 1>(src, &mut power_module_desc)?;
+/// qjson::from_str::<_, 2>(src, &mut power_module_desc)?;
 ///
 /// assert_eq!(power_module.description, Some("battery backup"));
 /// assert_eq!(power_module.adc_channels, [Some(1), Some(2)]);
@@ -108,263 +117,3148 @@ pub fn from_str<'a: 'b, 'b, S, const D: usize>(json: &'a str, desc: S) -> Result
 where
     S: Into<Schema<'a, 'b>>,
 {
-    Parser::<D>::new(json).parse(Some(&mut desc.into()))
+    from_str_with::<_, D>(json, desc, Options::new())
 }
 
-/// Validate a JSON string.
-pub fn validate<'a, const D: usize>(json: &'a str) -> Result<(), Error> {
-    Parser::<D>::new(json).parse(None)
+/// Deserialize a JSON string with the given parser options.
+///
+/// See [`from_str`] for more information.
+pub fn from_str_with<'a: 'b, 'b, S, const D: usize>(
+    json: &'a str,
+    desc: S,
+    opts: Options,
+) -> Result<(), Error>
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    Parser::<D>::new(json, opts).parse(Some(&mut desc.into()))
 }
 
-#[derive(Debug)]
-pub enum Schema<'a, 'b> {
-    Array(&'b mut [Schema<'a, 'b>]),
-    Bool(&'b mut Option<bool>),
-    Float(&'b mut Option<f64>),
-    Integer(&'b mut Option<i64>),
-    Object(&'b mut [(&'b str, Schema<'a, 'b>)]),
-    Str(&'b mut Option<&'a str>),
+/// Deserialize JSON from an iterator of characters (e.g., a decoder).
+///
+/// The characters are tokenized as they are read. Values bound to the
+/// schema borrow from the source, so the text of each token is encoded as
+/// UTF-8 into `scratch`; whitespace between tokens is not kept. If the
+/// tokens do not fit, [`ErrorKind::InsufficientBuffer`] is returned at the
+/// first character which does not fit.
+///
+/// As whitespace is dropped, values bound to [`Schema::Raw`] and
+/// [`Schema::ObjectCapture`] hold the text of their tokens without it. A
+/// [`Schema::Tagged`] needs to look ahead for its tag, which the iterator
+/// cannot, so binding one fails with [`ErrorKind::MismatchedTypes`].
+///
+/// # Example
+///
+/// ```
+/// let mut scratch = [0; 16];
+/// let mut name = None;
+/// let chars = r#"{ "name": "qjson" }"#.chars();
+/// let mut desc = [("name", qjson::Schema::Str(&mut name))];
+/// qjson::from_chars::<_, _, 1>(chars, &mut scratch, &mut desc).unwrap();
+/// assert_eq!(name, Some("qjson"));
+/// ```
+pub fn from_chars<'a: 'b, 'b, I, S, const D: usize>(
+    chars: I,
+    scratch: &'a mut [u8],
+    desc: S,
+) -> Result<(), Error>
+where
+    I: Iterator<Item = char>,
+    S: Into<Schema<'a, 'b>>,
+{
+    from_chars_with::<_, _, D>(chars, scratch, desc, Options::new())
 }
 
-#[derive(Debug, Clone)]
-pub struct Error {
-    lineno: usize,
-    col: usize,
-    kind: ErrorKind,
+/// Deserialize JSON from an iterator of characters with the given parser
+/// options.
+///
+/// See [`from_chars`] for more information.
+pub fn from_chars_with<'a: 'b, 'b, I, S, const D: usize>(
+    chars: I,
+    scratch: &'a mut [u8],
+    desc: S,
+    opts: Options,
+) -> Result<(), Error>
+where
+    I: Iterator<Item = char>,
+    S: Into<Schema<'a, 'b>>,
+{
+    let src = CharSource::new(chars, scratch);
+    let mut parser = Parser::<D, _>::with_tokenizer(Tokenizer::new(src, opts));
+    let res = parser.parse(Some(&mut desc.into()));
+
+    // the rest of the input was cut off, so the result is meaningless
+    if let Some(offset) = parser.tok.src.overflow {
+        return Err(Error {
+            offset,
+            ..parser.tok.err(InsufficientBuffer)
+        });
+    }
+    res
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum ErrorKind {
-    InsufficientArrayLength,
-    InvalidNumber,
-    MaxDepthExceeded,
-    MismatchedTypes,
-    MissingComma,
-    UnexpectedControlCharacterInString,
-    UnexpectedEof,
-    UnexpectedToken,
-    UnexpectedTrailingComma,
-    UnknownIdentifier,
-    UnknownStartOfToken,
-    UnterminatedString,
+/// Deserialize JSON from bytes (e.g., a receive buffer).
+///
+/// The bytes must be UTF-8; otherwise [`ErrorKind::InvalidUtf8`] is returned
+/// at the first invalid byte.
+/// The bytes are then parsed as with [`from_str`].
+///
+/// # Example
+///
+/// ```
+/// let mut name = None;
+/// let mut desc = [("name", qjson::Schema::Str(&mut name))];
+/// qjson::from_bytes::<_, 1>(br#"{"name":"qjson"}"#, &mut desc).unwrap();
+/// assert_eq!(name, Some("qjson"));
+///
+/// let err = qjson::validate_bytes::<1>(b"[\"\xff\"]").unwrap_err();
+/// assert_eq!(err.kind(), qjson::ErrorKind::InvalidUtf8);
+/// assert_eq!(err.offset(), 2);
+/// ```
+pub fn from_bytes<'a: 'b, 'b, S, const D: usize>(json: &'a [u8], desc: S) -> Result<(), Error>
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    from_str::<_, D>(from_utf8(json)?, desc)
 }
 
-struct Parser<'a, const D: usize> {
-    tok: Tokenizer<'a>,
-    peek: Option<Token<'a>>,
+/// Checks that `json` is UTF-8, pointing an error at the first invalid byte.
+fn from_utf8(json: &[u8]) -> Result<&str, Error> {
+    str::from_utf8(json).map_err(|e| {
+        let offset = e.valid_up_to();
+        // the bytes before the offset were just validated
+        let valid = str::from_utf8(&json[..offset]).unwrap_or_default();
+        let line = valid.rsplit('\n').next().unwrap_or_default();
+        Error {
+            lineno: valid.matches('\n').count() + 1,
+            col: line.chars().count() + 1,
+            kind: InvalidUtf8,
+            depth: None,
+            offset,
+        }
+    })
 }
 
-trait Clear {
-    fn clear(&mut self);
+/// Deserialize a JSON string into a borrowed schema.
+///
+/// Unlike [`from_str`] the schema is not consumed, so the same schema can be
+/// used to parse several documents in turn (e.g., to retry after an error).
+///
+/// If parsing fails every field in the schema is cleared, so no values from
+/// the failed document are left behind.
+///
+/// See [`from_str`] for more information.
+///
+/// # Example
+///
+/// ```
+/// let mut a = None;
+/// {
+///     let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+///     let mut schema = qjson::Schema::from(&mut desc);
+///
+///     assert!(qjson::from_str_mut::<1>(r#"{"a":1,}"#, &mut schema).is_err());
+///     qjson::from_str_mut::<1>(r#"{"a":2}"#, &mut schema).unwrap();
+/// }
+/// assert_eq!(a, Some(2));
+/// ```
+pub fn from_str_mut<'a, const D: usize>(
+    json: &'a str,
+    desc: &mut Schema<'a, '_>,
+) -> Result<(), Error> {
+    from_str_mut_with::<D>(json, desc, Options::new())
 }
 
-struct Tokenizer<'a> {
-    lineno: usize,
-    col: usize,
-    chars: Chars<'a>,
-    prev: &'a str,
+/// Deserialize a JSON string into a reusable schema with the given parser
+/// options.
+///
+/// See [`from_str_mut`] for more information.
+pub fn from_str_mut_with<'a, const D: usize>(
+    json: &'a str,
+    desc: &mut Schema<'a, '_>,
+    opts: Options,
+) -> Result<(), Error> {
+    let res = Parser::<D>::new(json, opts).parse(Some(&mut *desc));
+    if res.is_err() {
+        desc.clear();
+    }
+    res
 }
 
-#[derive(Copy, Clone, PartialEq)]
-enum Token<'a> {
-    Bool(bool),
-    BraceL,
-    BraceR,
-    BracketL,
-    BracketR,
-    Colon,
-    Comma,
-    Float(f64),
-    Integer(i64),
-    Null,
-    Str(&'a str),
+/// Deserialize a JSON string, returning the number of values bound.
+///
+/// Every scalar written to the schema counts towards the total, at any
+/// level of nesting. This can be used to check that all of the expected
+/// fields were present without inspecting each of them.
+///
+/// See [`from_str`] for more information.
+///
+/// # Example
+///
+/// ```
+/// let (mut a, mut b) = (None, None);
+/// let mut desc = [("a", qjson::Schema::Integer(&mut a)), ("b", qjson::Schema::Integer(&mut b))];
+/// let count = qjson::from_str_count::<_, 1>(r#"{"a":1}"#, &mut desc).unwrap();
+/// assert_eq!(count, 1);
+/// ```
+pub fn from_str_count<'a: 'b, 'b, S, const D: usize>(json: &'a str, desc: S) -> Result<usize, Error>
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    from_str_count_with::<_, D>(json, desc, Options::new())
 }
 
-// impl Error
+/// Deserialize a JSON string with the given parser options, returning the
+/// number of values bound.
+///
+/// See [`from_str_count`] for more information.
+pub fn from_str_count_with<'a: 'b, 'b, S, const D: usize>(
+    json: &'a str,
+    desc: S,
+    opts: Options,
+) -> Result<usize, Error>
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    let mut parser = Parser::<D>::new(json, opts);
+    parser.parse(Some(&mut desc.into()))?;
+    Ok(parser.bound)
+}
 
-impl Error {
-    /// Retrieves the line number the error was encountered on.
-    pub fn lineno(&self) -> usize {
-        self.lineno
-    }
+/// Deserialize a JSON string, returning the position of the root value.
+///
+/// The position is the `(lineno, col)` of the first character of the root
+/// value (i.e., the first non-whitespace character in the document), using
+/// the same numbering as [`Error::lineno`] and [`Error::col`].
+///
+/// See [`from_str`] for more information.
+///
+/// # Example
+///
+/// ```
+/// let mut a = None;
+/// let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+/// let pos = qjson::from_str_pos::<_, 1>("\n  {\"a\":1}", &mut desc).unwrap();
+/// assert_eq!(pos, (2, 3));
+/// ```
+pub fn from_str_pos<'a: 'b, 'b, S, const D: usize>(
+    json: &'a str,
+    desc: S,
+) -> Result<(usize, usize), Error>
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    from_str_pos_with::<_, D>(json, desc, Options::new())
+}
 
-    /// Retrieves the column the error was encountered on.
-    pub fn col(&self) -> usize {
-        self.col
-    }
+/// Deserialize a JSON string with the given parser options, returning the
+/// position of the root value.
+///
+/// See [`from_str_pos`] for more information.
+pub fn from_str_pos_with<'a: 'b, 'b, S, const D: usize>(
+    json: &'a str,
+    desc: S,
+    opts: Options,
+) -> Result<(usize, usize), Error>
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    let mut parser = Parser::<D>::new(json, opts);
+    parser.peek_next_tok()?;
+    let pos = parser.tok.start;
+    parser.parse(Some(&mut desc.into()))?;
+    Ok(pos)
+}
 
-    /// Retrieves the kind of error that occurred.
-    pub fn kind(&self) -> ErrorKind {
-        self.kind
-    }
+/// Deserialize as much of a JSON string as possible.
+///
+/// Parsing stops at the first error, but unlike [`from_str_mut`] the schema
+/// is left as it was at that point. Every value which was completely parsed
+/// before the error is bound:
+///
+/// * Scalars are only written once their whole value has been read, so the
+///   value being parsed when the error occurred is never partially written
+///   (e.g., a [`Schema::Flags`] array which fails part way is not bound).
+/// * Members of objects and arrays preceding the error are bound, even if
+///   the collection itself is never closed. This includes the pairs of a
+///   [`Schema::ObjectCapture`], whose count covers every complete pair.
+/// * The key index of a [`Schema::ObjectKeys`] group is recorded as soon
+///   as the key is read, before its value.
+///
+/// Returns the number of values bound alongside the error, if any.
+///
+/// See [`from_str`] for more information.
+///
+/// # Example
+///
+/// ```
+/// let (mut a, mut b) = (None, None);
+/// let mut desc = [("a", qjson::Schema::Integer(&mut a)), ("b", qjson::Schema::Integer(&mut b))];
+/// let (count, err) = qjson::from_str_best_effort::<_, 1>(r#"{"a":1,"b":tru"#, &mut desc);
+/// assert_eq!(count, 1);
+/// assert!(err.is_some());
+/// assert_eq!(a, Some(1));
+/// assert_eq!(b, None);
+/// ```
+pub fn from_str_best_effort<'a: 'b, 'b, S, const D: usize>(
+    json: &'a str,
+    desc: S,
+) -> (usize, Option<Error>)
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    from_str_best_effort_with::<_, D>(json, desc, Options::new())
 }
 
-// impl Schema
+/// Deserialize as much of a JSON string as possible with the given parser
+/// options.
+///
+/// See [`from_str_best_effort`] for more information.
+pub fn from_str_best_effort_with<'a: 'b, 'b, S, const D: usize>(
+    json: &'a str,
+    desc: S,
+    opts: Options,
+) -> (usize, Option<Error>)
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    let mut parser = Parser::<D>::new(json, opts);
+    let res = parser.parse(Some(&mut desc.into()));
+    (parser.bound, res.err())
+}
 
-impl<'a, 'b> From<&'b mut [Schema<'a, 'b>]> for Schema<'a, 'b> {
-    fn from(desc: &'b mut [Schema<'a, 'b>]) -> Self {
-        Self::Array(desc)
-    }
+/// Deserialize a JSON string without recursion.
+///
+/// Unlike [`from_str`], which recurses once per level of nesting, the
+/// parser keeps its state in `stack`: one [`Frame`] for every object or
+/// array which is open. Native stack usage is constant, so deeply nested
+/// (but legal) data cannot overflow a small call stack.
+///
+/// Both objects and arrays take a frame; input nested deeper than
+/// `stack.len()` fails with [`ErrorKind::MaxDepthExceeded`].
+///
+/// See [`from_str`] for more information.
+///
+/// # Example
+///
+/// ```
+/// let mut x = None;
+/// let mut inner = [("x", qjson::Schema::Integer(&mut x))];
+/// let mut arr = [qjson::Schema::Object(&mut inner)];
+/// let mut desc = [("a", qjson::Schema::Array(&mut arr))];
+///
+/// let mut stack = [qjson::Frame::new(); 3];
+/// qjson::from_str_stack(r#"{"a":[{"x":1}]}"#, &mut desc, &mut stack).unwrap();
+/// assert_eq!(x, Some(1));
+/// ```
+pub fn from_str_stack<'a: 'b, 'b, S>(
+    json: &'a str,
+    desc: S,
+    stack: &mut [Frame<'a>],
+) -> Result<(), Error>
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    from_str_stack_with(json, desc, stack, Options::new())
 }
 
-impl<'a, 'b, const N: usize> From<&'b mut [Schema<'a, 'b>; N]> for Schema<'a, 'b> {
-    fn from(desc: &'b mut [Schema<'a, 'b>; N]) -> Self {
-        Self::Array(&mut desc[..])
-    }
+/// Deserialize a JSON string without recursion with the given parser
+/// options.
+///
+/// See [`from_str_stack`] for more information.
+pub fn from_str_stack_with<'a: 'b, 'b, S>(
+    json: &'a str,
+    desc: S,
+    stack: &mut [Frame<'a>],
+    opts: Options,
+) -> Result<(), Error>
+where
+    S: Into<Schema<'a, 'b>>,
+{
+    Parser::<0>::new(json, opts).parse_stack(Some(&mut desc.into()), stack)
 }
 
-impl<'b> From<&'b mut Option<bool>> for Schema<'_, 'b> {
-    fn from(desc: &'b mut Option<bool>) -> Self {
-        Self::Bool(desc)
-    }
+/// Validate a JSON string without recursion.
+///
+/// See [`from_str_stack`] for more information.
+pub fn validate_stack<'a>(json: &'a str, stack: &mut [Frame<'a>]) -> Result<(), Error> {
+    validate_stack_with(json, stack, Options::new())
 }
 
-impl<'b> From<&'b mut Option<f64>> for Schema<'_, 'b> {
-    fn from(desc: &'b mut Option<f64>) -> Self {
-        Self::Float(desc)
-    }
+/// Validate a JSON string without recursion with the given parser options.
+///
+/// See [`from_str_stack`] for more information.
+pub fn validate_stack_with<'a>(
+    json: &'a str,
+    stack: &mut [Frame<'a>],
+    opts: Options,
+) -> Result<(), Error> {
+    Parser::<0>::new(json, opts).parse_stack(None, stack)
 }
 
-impl<'b> From<&'b mut Option<i64>> for Schema<'_, 'b> {
-    fn from(desc: &'b mut Option<i64>) -> Self {
-        Self::Integer(desc)
+/// Validate a JSON string.
+pub fn validate<'a, const D: usize>(json: &'a str) -> Result<(), Error> {
+    validate_with::<D>(json, Options::new())
+}
+
+/// Validate a JSON string with the given parser options.
+pub fn validate_with<const D: usize>(json: &str, opts: Options) -> Result<(), Error> {
+    Parser::<D>::new(json, opts).parse(None)
+}
+
+/// Validate JSON bytes.
+///
+/// See [`from_bytes`] for more information.
+pub fn validate_bytes<const D: usize>(json: &[u8]) -> Result<(), Error> {
+    validate::<D>(from_utf8(json)?)
+}
+
+/// Validate a JSON string, collecting as many errors as possible.
+///
+/// After an error the parser skips ahead to the next member of the root
+/// object or array and carries on. Recovery is conservative; the remainder
+/// of a member containing an error is not checked, and some errors may be
+/// knock-on effects of earlier ones.
+///
+/// The errors are written to `out` in document order, stopping once it is
+/// full, and the number of errors is returned.
+///
+/// # Example
+///
+/// ```
+/// let mut errs: [qjson::Error; 3] = Default::default();
+/// let n = qjson::validate_all::<1>(r#"{"a":tru,"b":1,"c":01}"#, &mut errs);
+/// assert_eq!(n, 2);
+/// assert_eq!(errs[0].kind(), qjson::ErrorKind::UnknownIdentifier);
+/// ```
+pub fn validate_all<const D: usize>(json: &str, out: &mut [Error]) -> usize {
+    validate_all_with::<D>(json, out, Options::new())
+}
+
+/// Validate a JSON string with the given parser options, collecting as many
+/// errors as possible.
+///
+/// See [`validate_all`] for more information.
+pub fn validate_all_with<const D: usize>(json: &str, out: &mut [Error], opts: Options) -> usize {
+    Parser::<D>::new(json, opts).validate_all(out)
+}
+
+/// Validate a JSON string, checking that every number survives a round trip
+/// through an `f64`.
+///
+/// Each number is parsed as an `f64` and formatted again; a number whose
+/// decimal value changes fails with [`ErrorKind::PrecisionLoss`]. Numbers
+/// which are not finite as an `f64` also fail. This is considerably slower
+/// than [`validate`].
+///
+/// # Example
+///
+/// ```
+/// qjson::validate_roundtrip::<1>("[0.1, 1.50, 9007199254740992]").unwrap();
+///
+/// let err = qjson::validate_roundtrip::<1>("[9007199254740993]").unwrap_err();
+/// assert_eq!(err.kind(), qjson::ErrorKind::PrecisionLoss);
+/// ```
+pub fn validate_roundtrip<const D: usize>(json: &str) -> Result<(), Error> {
+    validate_roundtrip_with::<D>(json, Options::new())
+}
+
+/// Validate a JSON string with the given parser options, checking that
+/// every number survives a round trip through an `f64`.
+///
+/// See [`validate_roundtrip`] for more information.
+pub fn validate_roundtrip_with<const D: usize>(json: &str, opts: Options) -> Result<(), Error> {
+    let mut parser = Parser::<D>::new(json, opts);
+    parser.tok.roundtrip = true;
+    parser.parse(None)
+}
+
+/// Peek at the kind of the root value of a JSON string.
+///
+/// Only the first token of the document is inspected; the rest of the data
+/// is *not* validated. This is useful to choose between alternative schemas
+/// before deserializing.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(qjson::peek_kind::<1>(r#" {"a":1}"#).unwrap(), qjson::ValueKind::Object);
+/// assert_eq!(qjson::peek_kind::<1>("[1, 2]").unwrap(), qjson::ValueKind::Array);
+/// ```
+pub fn peek_kind<const D: usize>(json: &str) -> Result<ValueKind, Error> {
+    Parser::<D>::new(json, Options::new()).peek_kind()
+}
+
+/// Deserialize a JSON string whose root value may be of any kind.
+///
+/// The kind of the root value picks which schema in `roots` it is bound
+/// to, and is returned. A root value whose schema is `None` is validated
+/// without binding anything.
+///
+/// # Example
+///
+/// ```
+/// let (mut a, mut b) = (None, None);
+/// let mut object = [("a", qjson::Schema::Str(&mut a))];
+/// let mut array = [qjson::Schema::Str(&mut b)];
+///
+/// let mut roots = qjson::RootSchemas {
+///     object: Some(qjson::Schema::from(&mut object)),
+///     array: Some(qjson::Schema::from(&mut array)),
+///     scalar: None,
+/// };
+/// let kind = qjson::from_str_any::<1>(r#"["x"]"#, &mut roots).unwrap();
+///
+/// assert_eq!(kind, qjson::ValueKind::Array);
+/// assert!(matches!(roots.array, Some(qjson::Schema::Array([qjson::Schema::Str(Some("x"))]))));
+/// ```
+pub fn from_str_any<'a, const D: usize>(
+    json: &'a str,
+    roots: &mut RootSchemas<'a, '_>,
+) -> Result<ValueKind, Error> {
+    from_str_any_with::<D>(json, roots, Options::new())
+}
+
+/// Deserialize a JSON string whose root value may be of any kind with the
+/// given parser options.
+///
+/// See [`from_str_any`] for more information.
+pub fn from_str_any_with<'a, const D: usize>(
+    json: &'a str,
+    roots: &mut RootSchemas<'a, '_>,
+    opts: Options,
+) -> Result<ValueKind, Error> {
+    let mut parser = Parser::<D>::new(json, opts);
+    let kind = parser.peek_kind()?;
+    let desc = match kind {
+        ValueKind::Object => roots.object.as_mut(),
+        ValueKind::Array => roots.array.as_mut(),
+        _ => roots.scalar.as_mut(),
+    };
+    parser.parse(desc)?;
+    Ok(kind)
+}
+
+/// Collect the keys of the root object of a JSON string.
+///
+/// The keys are borrowed from the source and written to `out` in document
+/// order; the number of keys is returned. Values are validated, but not
+/// bound. This is useful to discover the shape of a document.
+///
+/// # Errors
+///
+/// * [`ErrorKind::MismatchedTypes`] if the root value isn't an object.
+/// * [`ErrorKind::InsufficientArrayLength`] if there are more keys than
+///   `out` can hold.
+///
+/// # Example
+///
+/// ```
+/// let mut keys = [""; 4];
+/// let n = qjson::root_keys::<2>(r#"{"a":1,"b":{"c":2}}"#, &mut keys).unwrap();
+/// assert_eq!(&keys[..n], ["a", "b"]);
+/// ```
+pub fn root_keys<'a, const D: usize>(json: &'a str, out: &mut [&'a str]) -> Result<usize, Error> {
+    Parser::<D>::new(json, Options::new()).root_keys(out)
+}
+
+/// Serialize the values bound to a schema as compact JSON.
+///
+/// This is the inverse of [`from_str`]: the schema is walked and its values
+/// are written to `w` without allocating. Unbound fields are written as
+/// `null`, as are non-finite floats. Strings are escaped, while
+/// [`Schema::Raw`] and the values of [`Schema::ObjectCapture`] are written
+/// verbatim. Floats use the shortest form which parses back to the same
+/// `f64`, so finite values round trip through the deserializer.
+///
+/// Enums are written as the name of their variant and flags as the array of
+/// names whose bits are all set.
+///
+/// Keys, and the names of variants, flags and tagged arms, are matched
+/// against the source text with any escapes left as is, so they are also
+/// written verbatim between quotes.
+///
+/// # Example
+///
+/// ```
+/// let (mut id, mut name, mut scale) = (Some(7), Some("a \"b\""), Some(0.1));
+/// let mut desc = [
+///     ("id", qjson::Schema::Integer(&mut id)),
+///     ("name", qjson::Schema::Str(&mut name)),
+///     ("scale", qjson::Schema::Float(&mut scale)),
+/// ];
+///
+/// let mut out = String::new();
+/// qjson::to_writer(&mut out, &mut desc).unwrap();
+/// assert_eq!(out, r#"{"id":7,"name":"a \"b\"","scale":0.1}"#);
+/// ```
+pub fn to_writer<'a: 'b, 'b, W, S>(w: &mut W, desc: S) -> fmt::Result
+where
+    W: fmt::Write,
+    S: Into<Schema<'a, 'b>>,
+{
+    write_value(w, &desc.into())
+}
+
+/// Split a JSON string into tokens, without checking how they nest.
+///
+/// Each token is yielded with its [`Span`] in the source. Iteration stops
+/// after the first error. This is the lexer used by the parser, so tokens
+/// are checked exactly as they are when deserializing.
+///
+/// # Example
+///
+/// ```
+/// let src = r#"{"a": [1, true]}"#;
+/// let mut tokens = qjson::tokens(src).map(Result::unwrap);
+///
+/// assert_eq!(tokens.next().unwrap().0, qjson::Token::BraceL);
+///
+/// let (tok, span) = tokens.next().unwrap();
+/// assert_eq!(tok, qjson::Token::Str { text: "a", escaped: false });
+/// assert_eq!(&src[span.start..span.end], r#""a""#);
+/// assert_eq!((span.lineno, span.col), (1, 2));
+///
+/// assert_eq!(tokens.count(), 7);
+/// ```
+pub fn tokens(json: &str) -> Tokens<'_> {
+    tokens_with(json, Options::new())
+}
+
+/// Split a JSON string into tokens with the given options.
+///
+/// See [`tokens`] for more information.
+pub fn tokens_with(json: &str, opts: Options) -> Tokens<'_> {
+    Tokens {
+        tok: Tokenizer::new(StrSource::new(json), opts),
+        done: false,
+    }
+}
+
+/// Build a (possibly nested) schema description.
+///
+/// Objects are written as `{ "key": value, .. }` and arrays as
+/// `[value, ..]`; values are either nested objects and arrays or
+/// [`Schema`] variants without the `Schema::` prefix. The macro expands to
+/// the top level array, which must be bound with `let` so the nested
+/// descriptions live long enough.
+///
+/// # Example
+///
+/// ```
+/// # fn _example() -> Result<(), qjson::Error> {
+/// let src = r#"{"name": "adc", "channels": [1, 2], "gain": {"db": 6.0}}"#;
+///
+/// let mut name = None;
+/// let (mut ch0, mut ch1) = (None, None);
+/// let mut db = None;
+///
+/// let mut desc = qjson::schema!({
+///     "name": Str(&mut name),
+///     "channels": [Integer(&mut ch0), Integer(&mut ch1)],
+///     "gain": { "db": Float(&mut db) },
+/// });
+///
+/// qjson::from_str::<_, 2>(src, &mut desc)?;
+///
+/// assert_eq!(name, Some("adc"));
+/// assert_eq!((ch0, ch1), (Some(1), Some(2)));
+/// assert_eq!(db, Some(6.0));
+/// # Ok(())
+/// # }
+/// # _example().unwrap();
+/// ```
+#[macro_export]
+macro_rules! schema {
+    ({ $($body:tt)* }) => {
+        $crate::schema!(@obj [] $($body)*)
+    };
+    ([ $($body:tt)* ]) => {
+        $crate::schema!(@arr [] $($body)*)
+    };
+
+    (@obj [$($out:tt)*]) => {
+        [$($out)*]
+    };
+    (@obj [$($out:tt)*] $key:literal : { $($val:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::schema!(@obj [
+            $($out)* ($key, $crate::Schema::Object(&mut $crate::schema!({ $($val)* }))),
+        ] $($($rest)*)?)
+    };
+    (@obj [$($out:tt)*] $key:literal : [ $($val:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::schema!(@obj [
+            $($out)* ($key, $crate::Schema::Array(&mut $crate::schema!([ $($val)* ]))),
+        ] $($($rest)*)?)
+    };
+    (@obj [$($out:tt)*] $key:literal : $kind:ident $args:tt $(, $($rest:tt)*)?) => {
+        $crate::schema!(@obj [
+            $($out)* ($key, $crate::Schema::$kind $args),
+        ] $($($rest)*)?)
+    };
+
+    (@arr [$($out:tt)*]) => {
+        [$($out)*]
+    };
+    (@arr [$($out:tt)*] { $($val:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::schema!(@arr [
+            $($out)* $crate::Schema::Object(&mut $crate::schema!({ $($val)* })),
+        ] $($($rest)*)?)
+    };
+    (@arr [$($out:tt)*] [ $($val:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::schema!(@arr [
+            $($out)* $crate::Schema::Array(&mut $crate::schema!([ $($val)* ])),
+        ] $($($rest)*)?)
+    };
+    (@arr [$($out:tt)*] $kind:ident $args:tt $(, $($rest:tt)*)?) => {
+        $crate::schema!(@arr [
+            $($out)* $crate::Schema::$kind $args,
+        ] $($($rest)*)?)
+    };
+}
+
+/// Build the schema description of a fixed-layout array from its targets.
+///
+/// Each target is converted with [`Schema::from`], so its type picks the
+/// variant (e.g., `&mut Option<i64>` becomes [`Schema::Integer`]). Like
+/// [`schema!`], the macro expands to an array which must be bound with
+/// `let`; it is the same array of schemas [`Schema::Array`] takes.
+///
+/// # Example
+///
+/// ```
+/// let (mut version, mut scale, mut name) = (None, None, None);
+///
+/// let mut record = qjson::tuple_schema!(&mut version, &mut scale, &mut name);
+/// let mut desc = [("record", qjson::Schema::Array(&mut record))];
+///
+/// qjson::from_str::<_, 2>(r#"{"record": [2, 0.5, "adc"]}"#, &mut desc).unwrap();
+///
+/// assert_eq!((version, scale, name), (Some(2), Some(0.5), Some("adc")));
+/// ```
+#[macro_export]
+macro_rules! tuple_schema {
+    ($($target:expr),* $(,)?) => {
+        [$($crate::Schema::from($target)),*]
+    };
+}
+
+pub enum Schema<'a, 'b> {
+    Array(&'b mut [Schema<'a, 'b>]),
+    Bool(&'b mut Option<bool>),
+    /// A string which must be one of `variants`; the index of the matching
+    /// variant is written to `out`.
+    Enum {
+        out: &'b mut Option<usize>,
+        variants: &'b [&'b str],
+    },
+    /// An array of flag names; the bits of each name are OR-ed together
+    /// and written to `out`.
+    Flags {
+        out: &'b mut Option<u64>,
+        names: &'b [(&'b str, u64)],
+    },
+    Float(&'b mut Option<f64>),
+    /// An array of numbers written to the slice by index; the array must
+    /// have exactly as many elements as the slice.
+    FloatArray(&'b mut [f64]),
+    I8(&'b mut Option<i8>),
+    I16(&'b mut Option<i16>),
+    I32(&'b mut Option<i32>),
+    /// An array of integers written to the slice by index; the array must
+    /// have exactly as many elements as the slice.
+    IntArray(&'b mut [i64]),
+    /// An object used as a sparse array; each key is parsed as an index
+    /// into the slice (e.g., `{"2": 5}` binds the schema at index 2).
+    IndexedObject(&'b mut [Schema<'a, 'b>]),
+    Integer(&'b mut Option<i64>),
+    /// A duration in microseconds; see [`Schema::Millis`].
+    Micros(&'b mut Option<u64>),
+    /// A duration in milliseconds, stored as the raw count.
+    ///
+    /// The value must be a non-negative integer; negative durations fail
+    /// with [`ErrorKind::NumberOutOfRange`].
+    Millis(&'b mut Option<u64>),
+    Object(&'b mut [(&'b str, Schema<'a, 'b>)]),
+    /// An object of unknown shape; each key is captured alongside the raw
+    /// JSON text of its value (e.g., strings keep their quotes), and the
+    /// number of pairs is written to the `usize`.
+    ///
+    /// Pairs are stored in source order, so the index of a pair in the
+    /// slice is the position of its key in the object. Duplicate keys are
+    /// each captured, regardless of [`Options::first_key_wins`].
+    ObjectCapture(&'b mut [(&'a str, &'a str)], &'b mut usize),
+    /// An object where each schema is shared by a group of keys; the index
+    /// of the key that matched within its group is written alongside.
+    ObjectKeys(&'b mut [(&'b [&'b str], &'b mut Option<usize>, Schema<'a, 'b>)]),
+    /// An object which records whether it was present; `present` is set
+    /// when the object is entered, and cleared if it is `null`.
+    ///
+    /// Nesting these tells an absent object apart from one which was
+    /// present but missing some of its members.
+    OptionalObject {
+        present: &'b mut bool,
+        fields: &'b mut [(&'b str, Schema<'a, 'b>)],
+    },
+    /// Any value, captured as its source text without being interpreted
+    /// (e.g., `{"a": 1}` keeps its braces and whitespace).
+    ///
+    /// This defers parsing of a value, or forwards it elsewhere verbatim.
+    Raw(&'b mut Option<&'a str>),
+    /// A string borrowed from the source.
+    ///
+    /// Strings containing escapes cannot be borrowed as they are, and fail
+    /// with [`ErrorKind::EscapeInBorrowedString`]; decode them with
+    /// [`Schema::StrBuf`] instead.
+    Str(&'b mut Option<&'a str>),
+    /// A string with its escape sequences decoded into the buffer; the
+    /// output points at the decoded text.
+    ///
+    /// Each string bound consumes the front of the buffer, leaving the rest
+    /// for later values (e.g., duplicate keys). Malformed escapes, including
+    /// unpaired surrogates, fail with [`ErrorKind::InvalidEscape`].
+    StrBuf(&'a mut [u8], &'b mut Option<&'a str>),
+    /// A string with its escape sequences decoded into the buffer, which is
+    /// zero-filled past the end of the text.
+    ///
+    /// A string longer than the buffer fails with
+    /// [`ErrorKind::InsufficientBuffer`], leaving the buffer unchanged.
+    StrFixed(&'b mut [u8]),
+    /// An object whose string member `tag` selects which of the `arms` the
+    /// rest of its members are bound to; the index of the arm is written to
+    /// `out`.
+    ///
+    /// The tag may appear anywhere in the object. It is found before the
+    /// object is bound, so each arm only describes its own members (the
+    /// tag is not a member of any arm). A tag which does not name an arm,
+    /// or a missing tag, fails with [`ErrorKind::UnknownIdentifier`].
+    Tagged {
+        tag: &'b str,
+        out: &'b mut Option<usize>,
+        arms: &'b mut [(&'b str, &'b mut [(&'b str, Schema<'a, 'b>)])],
+    },
+    U8(&'b mut Option<u8>),
+    U16(&'b mut Option<u16>),
+    U32(&'b mut Option<u32>),
+    U64(&'b mut Option<u64>),
+    /// A string which is only bound if `valid` returns `true` for it;
+    /// otherwise parsing fails with [`ErrorKind::ValidationFailed`] at the
+    /// start of the string.
+    ///
+    /// The string is borrowed from the source as with [`Schema::Str`], so
+    /// strings containing escapes fail with
+    /// [`ErrorKind::EscapeInBorrowedString`].
+    ValidatedStr {
+        out: &'b mut Option<&'a str>,
+        valid: &'b mut dyn FnMut(&str) -> bool,
+    },
+}
+
+/// Parser options.
+///
+/// The default options only accept strict RFC 8259 JSON.
+///
+/// ```
+/// let opts = qjson::Options {
+///     lenient: true,
+///     ..qjson::Options::new()
+/// };
+///
+/// qjson::validate_with::<1>("{'key': 'value'}", opts).unwrap();
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    /// Accept non-standard extensions to the JSON grammar:
+    ///
+    /// * Strings may be delimited by single quotes (`'...'`).
+    /// * The strings `"true"` and `"false"` may be bound to a
+    ///   [`Schema::Bool`].
+    /// * Vertical tabs (`\x0B`), form feeds (`\x0C`) and any characters in
+    ///   [`Options::whitespace`] are skipped as whitespace.
+    pub lenient: bool,
+
+    /// Additional characters to skip as whitespace in lenient mode.
+    ///
+    /// Ignored unless [`Options::lenient`] is set.
+    pub whitespace: &'static [char],
+
+    /// Treat empty or whitespace-only input as a successful no-op instead
+    /// of failing with [`ErrorKind::UnexpectedEof`].
+    pub allow_empty: bool,
+
+    /// Keep the first occurrence of a duplicate key instead of the last.
+    ///
+    /// Later occurrences are skipped entirely, even when the first was
+    /// `null` or a nested object. Fields past the first 64 of a schema are
+    /// checked by scanning the keys already read, so binding them costs time
+    /// linear in the size of the object.
+    pub first_key_wins: bool,
+
+    /// Leave scalar fields untouched when bound to `null`, instead of
+    /// clearing them.
+    ///
+    /// This allows fields to be seeded with defaults which `null` keeps.
+    /// Objects and arrays bound to `null` are still cleared.
+    pub null_keeps_default: bool,
+
+    /// The most members a single object or array may have, failing with
+    /// [`ErrorKind::ContainerTooLarge`] otherwise.
+    ///
+    /// This applies to every object and array in the input, whether or not
+    /// it is bound to a schema.
+    pub max_container_len: Option<usize>,
+
+    /// Count arrays, as well as objects, as a level of nesting towards the
+    /// depth limit `D`.
+    ///
+    /// This is set by default, so `D` bounds all structural nesting and
+    /// input such as `[[[[...]]]]` cannot exhaust the stack. Clearing it
+    /// restores the old behaviour of only counting objects.
+    pub count_arrays: bool,
+}
+
+/// A level of nesting for the non-recursive parser; see [`from_str_stack`].
+#[derive(Debug, Copy, Clone)]
+pub struct Frame<'a> {
+    array: bool,
+    member: usize,
+    field: Option<usize>,
+    key: &'a str,
+    start: usize,
+    seen: Seen,
+}
+
+/// The fields of an object schema bound so far, by position; see
+/// [`Options::first_key_wins`].
+///
+/// The first 64 fields are tracked in a bitmask; later fields are found by
+/// scanning the keys read since the first member.
+#[derive(Debug, Default, Copy, Clone)]
+struct Seen {
+    bits: u64,
+    /// The field of the first member, and the text position after its colon.
+    first: Option<(Option<usize>, usize)>,
+}
+
+/// Schemas for each kind of root value; see [`from_str_any`].
+#[derive(Debug, Default)]
+pub struct RootSchemas<'a, 'b> {
+    /// Bound when the root value is an object.
+    pub object: Option<Schema<'a, 'b>>,
+    /// Bound when the root value is an array.
+    pub array: Option<Schema<'a, 'b>>,
+    /// Bound when the root value is a string, number, boolean or `null`.
+    pub scalar: Option<Schema<'a, 'b>>,
+}
+
+/// The kind of a JSON value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ValueKind {
+    Array,
+    Bool,
+    Null,
+    Number,
+    Object,
+    Str,
+}
+
+/// An iterator over the tokens of a JSON string; see [`tokens`].
+pub struct Tokens<'a> {
+    tok: Tokenizer<'a>,
+    done: bool,
+}
+
+/// The position of a [`Token`] in the source.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Span {
+    /// The line the token starts on, numbered as [`Error::lineno`].
+    pub lineno: usize,
+    /// The column the token starts on, numbered as [`Error::col`].
+    pub col: usize,
+    /// The byte offset of the start of the token.
+    pub start: usize,
+    /// The byte offset just past the end of the token.
+    pub end: usize,
+}
+
+/// Renders the values bound to a schema; see [`Schema::display`].
+pub struct SchemaDisplay<'s, 'a, 'b>(&'s Schema<'a, 'b>);
+
+#[derive(Debug, Clone)]
+pub struct Error {
+    lineno: usize,
+    col: usize,
+    kind: ErrorKind,
+    depth: Option<usize>,
+    offset: usize,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ErrorKind {
+    ContainerTooLarge,
+    EscapeInBorrowedString,
+    InsufficientArrayLength,
+    InsufficientBuffer,
+    InvalidEscape,
+    InvalidNumber,
+    InvalidUtf8,
+    MaxDepthExceeded,
+    MismatchedBracket,
+    MismatchedTypes,
+    MissingComma,
+    NumberOutOfRange,
+    PrecisionLoss,
+    UnexpectedControlCharacterInString,
+    UnexpectedEof,
+    UnexpectedToken,
+    UnexpectedTrailingComma,
+    UnknownIdentifier,
+    UnknownStartOfToken,
+    UnknownVariant,
+    UnterminatedString,
+    ValidationFailed,
+}
+
+struct Parser<'a, const D: usize, S = StrSource<'a>> {
+    tok: Tokenizer<'a, S>,
+    peek: Option<Token<'a>>,
+    bound: usize,
+}
+
+/// Where the parser resumed after skipping past an error.
+enum Resync {
+    /// After a comma separating two members of the root value.
+    Member,
+    /// After the end of the root value.
+    Closed,
+    /// At the end of the input, inside the root value.
+    Eof,
+}
+
+trait Clear {
+    fn clear(&mut self);
+}
+
+trait Fields<'a, 'b> {
+    fn position(&self, key: &str) -> Option<usize>;
+    fn field(&mut self, i: usize, key: &str) -> &mut Schema<'a, 'b>;
+    fn clear(&mut self);
+}
+
+#[derive(Clone)]
+struct Tokenizer<'a, S = StrSource<'a>> {
+    opts: Options,
+    roundtrip: bool,
+    lineno: usize,
+    col: usize,
+    start: (usize, usize),
+    /// The source offset of the last token.
+    start_offset: usize,
+    /// The text position of the last token.
+    start_pos: usize,
+    src: S,
+    /// The source offset of the most recently popped char.
+    prev: usize,
+    /// The text position of the most recently popped char.
+    prev_pos: usize,
+    _src: PhantomData<&'a str>,
+}
+
+/// The characters read by a [`Tokenizer`], and the text its tokens borrow.
+///
+/// Offsets count the bytes of the source read so far; positions index the
+/// text, which only holds the characters which are part of tokens.
+trait Source<'a> {
+    /// Pop the next character.
+    fn next_char(&mut self) -> Option<char>;
+
+    /// The next character, without popping it.
+    fn peek_char(&mut self) -> Option<char>;
+
+    /// The byte offset of the next character in the source.
+    fn offset(&self) -> usize;
+
+    /// The position of the next character in the text.
+    fn pos(&self) -> usize;
+
+    /// Drop the most recently popped character (i.e., whitespace) from the
+    /// text.
+    fn skip(&mut self);
+
+    /// The text between two positions.
+    fn text(&self, start: usize, end: usize) -> &'a str;
+
+    /// A copy of the source which can be read ahead, if there is one.
+    fn fork(&self) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+/// A [`Source`] over a string, which is its own text.
+#[derive(Clone)]
+struct StrSource<'a> {
+    json: &'a str,
+    chars: Chars<'a>,
+}
+
+/// A [`Source`] over an iterator of characters, which encodes the text of
+/// its tokens into a scratch buffer.
+struct CharSource<'a, I> {
+    chars: I,
+    peeked: Option<char>,
+    offset: usize,
+    buf: *mut u8,
+    cap: usize,
+    pos: usize,
+    /// The length of the most recently popped character, if it was written.
+    last: Option<usize>,
+    /// The offset of the first character which did not fit in the buffer.
+    overflow: Option<usize>,
+    _buf: PhantomData<&'a mut [u8]>,
+}
+
+/// A JSON token; see [`tokens`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Token<'a> {
+    Bool(bool),
+    BraceL,
+    BraceR,
+    BracketL,
+    BracketR,
+    Colon,
+    Comma,
+    Null,
+    /// The text of a number, checked against the JSON number grammar.
+    Number(&'a str),
+    /// A string.
+    Str {
+        /// The text between the quotes; escapes are left as is.
+        text: &'a str,
+        /// Whether the text contains any escapes.
+        escaped: bool,
+    },
+}
+
+/// Does the number contain only an integer part?
+///
+/// Numbers with an exponent are floats, even if their value is whole.
+fn is_integer(n: &str) -> bool {
+    !n.contains(['.', 'e', 'E'])
+}
+
+/// Does the number match the JSON number grammar?
+fn is_valid_number(n: &str) -> bool {
+    fn skip_digits(mut s: &[u8]) -> (&[u8], usize) {
+        let mut count = 0;
+        while let [b'0'..=b'9', rest @ ..] = s {
+            s = rest;
+            count += 1;
+        }
+        (s, count)
+    }
+
+    let mut s = n.as_bytes();
+
+    if let [b'-', rest @ ..] = s {
+        s = rest;
+    }
+
+    s = match s {
+        [b'0', rest @ ..] => rest,
+        [b'1'..=b'9', ..] => skip_digits(s).0,
+        _ => return false,
+    };
+
+    if let [b'.', rest @ ..] = s {
+        match skip_digits(rest) {
+            (_, 0) => return false,
+            (rest, _) => s = rest,
+        }
+    }
+
+    if let [b'e' | b'E', rest @ ..] = s {
+        s = match rest {
+            [b'+' | b'-', rest @ ..] => rest,
+            _ => rest,
+        };
+        match skip_digits(s) {
+            (_, 0) => return false,
+            (rest, _) => s = rest,
+        }
+    }
+
+    s.is_empty()
+}
+
+/// Decodes the escape sequences in the string `s` into `buf`, returning the
+/// length of the decoded text.
+///
+/// `\'` is only an escape in lenient mode.
+fn unescape(s: &str, buf: &mut [u8], lenient: bool) -> Result<usize, ErrorKind> {
+    let mut len = 0;
+    decode(s, lenient, |c| {
+        let dst = buf
+            .get_mut(len..len + c.len_utf8())
+            .ok_or(InsufficientBuffer)?;
+        len += c.encode_utf8(dst).len();
+        Ok(())
+    })?;
+    Ok(len)
+}
+
+/// Returns the length of the string `s` once its escape sequences are
+/// decoded, checking that they are well formed.
+fn unescaped_len(s: &str, lenient: bool) -> Result<usize, ErrorKind> {
+    let mut len = 0;
+    decode(s, lenient, |c| {
+        len += c.len_utf8();
+        Ok(())
+    })?;
+    Ok(len)
+}
+
+/// Decodes the escape sequences in the string `s`, passing each character
+/// of the decoded text to `out`.
+fn decode<F>(s: &str, lenient: bool, mut out: F) -> Result<(), ErrorKind>
+where
+    F: FnMut(char) -> Result<(), ErrorKind>,
+{
+    fn hex4(chars: &mut Chars<'_>) -> Result<u32, ErrorKind> {
+        let mut n = 0;
+        for _ in 0..4 {
+            let digit = chars.next().and_then(|c| c.to_digit(16));
+            n = n * 16 + digit.ok_or(InvalidEscape)?;
+        }
+        Ok(n)
+    }
+
+    let mut chars = s.chars();
+
+    while let Some(mut c) = chars.next() {
+        if c == '\\' {
+            c = match chars.next().ok_or(InvalidEscape)? {
+                c @ ('"' | '\\' | '/') => c,
+                '\'' if lenient => '\'',
+                'b' => '\x08',
+                'f' => '\x0C',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let mut n = hex4(&mut chars)?;
+                    // a high surrogate must be followed by a low surrogate
+                    if (0xD800..0xDC00).contains(&n) {
+                        if chars.next() != Some('\\') || chars.next() != Some('u') {
+                            return Err(InvalidEscape);
+                        }
+                        match hex4(&mut chars)? {
+                            lo @ 0xDC00..=0xDFFF => {
+                                n = 0x10000 + ((n - 0xD800) << 10) + (lo - 0xDC00)
+                            }
+                            _ => return Err(InvalidEscape),
+                        }
+                    }
+                    // lone low surrogates are not characters
+                    char::from_u32(n).ok_or(InvalidEscape)?
+                }
+                _ => return Err(InvalidEscape),
+            };
+        }
+
+        out(c)?;
+    }
+
+    Ok(())
+}
+
+/// Does the number keep its decimal value when converted to an `f64`?
+///
+/// The `f64` is formatted as the shortest string which parses back to it,
+/// and the two decimal values are compared.
+fn roundtrips(n: &str) -> bool {
+    let f = match n.parse::<f64>() {
+        Ok(f) if f.is_finite() => f,
+        _ => return false,
+    };
+
+    let mut buf = NumBuf::default();
+    if fmt::Write::write_fmt(&mut buf, format_args!("{:e}", f)).is_err() {
+        return false;
+    }
+
+    match core::str::from_utf8(&buf.buf[..buf.len]) {
+        Ok(formatted) => Decimal::new(n) == Decimal::new(formatted),
+        Err(_) => false,
+    }
+}
+
+/// A formatting buffer large enough for any `f64` in exponent notation.
+#[derive(Default)]
+struct NumBuf {
+    buf: [u8; 32],
+    len: usize,
+}
+
+impl fmt::Write for NumBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        let dst = self.buf.get_mut(self.len..end).ok_or(fmt::Error)?;
+        dst.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// A decimal number normalized to `0.digits * 10^exp`, so that numbers with
+/// the same value compare equal regardless of how they were written.
+struct Decimal<'a> {
+    neg: bool,
+    int: &'a str,
+    frac: &'a str,
+    /// Leading zeros to skip in `int` followed by `frac`.
+    lead: usize,
+    /// Significant digits following the leading zeros.
+    len: usize,
+    exp: i64,
+}
+
+impl<'a> Decimal<'a> {
+    fn new(n: &'a str) -> Self {
+        let (neg, n) = match n.strip_prefix('-') {
+            Some(n) => (true, n),
+            None => (false, n),
+        };
+
+        let (mantissa, exp) = match n.find(['e', 'E']) {
+            Some(i) => {
+                let e = &n[i + 1..];
+                let exp = e.bytes().filter(u8::is_ascii_digit).fold(0_i64, |exp, d| {
+                    exp.saturating_mul(10).saturating_add(i64::from(d - b'0'))
+                });
+                (&n[..i], if e.starts_with('-') { -exp } else { exp })
+            }
+            None => (n, 0),
+        };
+
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        let digits = || int.bytes().chain(frac.bytes());
+        let total = int.len() + frac.len();
+        let lead = digits().take_while(|&d| d == b'0').count();
+        let trail = digits().rev().take_while(|&d| d == b'0').count();
+        let len = total.saturating_sub(lead + trail);
+
+        Self {
+            neg,
+            int,
+            frac,
+            lead,
+            len,
+            exp: exp.saturating_add(int.len() as i64 - lead as i64),
+        }
+    }
+
+    fn digits(&self) -> impl Iterator<Item = u8> + 'a {
+        let int = self.int.bytes();
+        int.chain(self.frac.bytes()).skip(self.lead).take(self.len)
+    }
+}
+
+impl PartialEq for Decimal<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.len, other.len) {
+            (0, 0) => true,
+            (a, b) if a != b => false,
+            _ => self.neg == other.neg && self.exp == other.exp && self.digits().eq(other.digits()),
+        }
+    }
+}
+
+// impl Error
+
+impl Error {
+    /// Retrieves the line number the error was encountered on.
+    pub fn lineno(&self) -> usize {
+        self.lineno
+    }
+
+    /// Retrieves the column the error was encountered on.
+    ///
+    /// Columns are numbered from 1 and count characters (`char`s), not
+    /// bytes, so a multi-byte character such as `é` or `😀` occupies a
+    /// single column.
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    /// Retrieves the kind of error that occurred.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Retrieves the depth reached by the input for a
+    /// [`MaxDepthExceeded`](ErrorKind::MaxDepthExceeded) error.
+    ///
+    /// This is the deepest nesting of objects and arrays (only objects, if
+    /// [`Options::count_arrays`] is cleared) found within the first value
+    /// that exceeded the limit, i.e. the smallest `D` that would have
+    /// accepted it. Values later in the input are not inspected. Returns
+    /// `None` for every other kind of error.
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// Retrieves the byte offset in the source of the character at
+    /// [`lineno`](Error::lineno) and [`col`](Error::col).
+    ///
+    /// An error at the end of the input is at the length of the source, so
+    /// the offset can always be used to slice it (e.g., `&src[offset..]`).
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Default for Error {
+    /// A placeholder error at the start of the input, for filling the
+    /// buffer passed to [`validate_all`].
+    fn default() -> Self {
+        Self {
+            lineno: 1,
+            col: 1,
+            kind: ValidationFailed,
+            depth: None,
+            offset: 0,
+        }
+    }
+}
+
+// impl Options
+
+impl Options {
+    /// Creates the default (strict) parser options.
+    pub const fn new() -> Self {
+        Self {
+            lenient: false,
+            whitespace: &[],
+            allow_empty: false,
+            first_key_wins: false,
+            null_keeps_default: false,
+            max_container_len: None,
+            count_arrays: true,
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// impl Frame
+
+impl Frame<'_> {
+    /// Creates an unused frame.
+    pub const fn new() -> Self {
+        Self {
+            array: false,
+            member: 0,
+            field: None,
+            key: "",
+            start: 0,
+            seen: Seen {
+                bits: 0,
+                first: None,
+            },
+        }
+    }
+}
+
+impl Default for Frame<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// impl Schema
+
+impl<'a, 'b> From<&'b mut [Schema<'a, 'b>]> for Schema<'a, 'b> {
+    fn from(desc: &'b mut [Schema<'a, 'b>]) -> Self {
+        Self::Array(desc)
+    }
+}
+
+impl<'a, 'b, const N: usize> From<&'b mut [Schema<'a, 'b>; N]> for Schema<'a, 'b> {
+    fn from(desc: &'b mut [Schema<'a, 'b>; N]) -> Self {
+        Self::Array(&mut desc[..])
+    }
+}
+
+impl<'b> From<&'b mut Option<bool>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<bool>) -> Self {
+        Self::Bool(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<f64>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<f64>) -> Self {
+        Self::Float(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<i64>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<i64>) -> Self {
+        Self::Integer(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<i8>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<i8>) -> Self {
+        Self::I8(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<i16>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<i16>) -> Self {
+        Self::I16(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<i32>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<i32>) -> Self {
+        Self::I32(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<u8>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<u8>) -> Self {
+        Self::U8(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<u16>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<u16>) -> Self {
+        Self::U16(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<u32>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<u32>) -> Self {
+        Self::U32(desc)
+    }
+}
+
+impl<'b> From<&'b mut Option<u64>> for Schema<'_, 'b> {
+    fn from(desc: &'b mut Option<u64>) -> Self {
+        Self::U64(desc)
+    }
+}
+
+impl<'a, 'b> From<&'b mut [(&'b str, Schema<'a, 'b>)]> for Schema<'a, 'b> {
+    fn from(desc: &'b mut [(&'b str, Schema<'a, 'b>)]) -> Self {
+        Self::Object(desc)
+    }
+}
+
+impl<'a, 'b, const N: usize> From<&'b mut [(&'b str, Schema<'a, 'b>); N]> for Schema<'a, 'b> {
+    fn from(desc: &'b mut [(&'b str, Schema<'a, 'b>); N]) -> Self {
+        Self::Object(&mut desc[..])
+    }
+}
+
+impl<'a, 'b> From<&'b mut Option<&'a str>> for Schema<'a, 'b> {
+    fn from(desc: &'b mut Option<&'a str>) -> Self {
+        Self::Str(desc)
+    }
+}
+
+/// A type which describes its own [`Schema`].
+///
+/// Implementing this once per type keeps the descriptor next to the type
+/// rather than at every call site.
+///
+/// The descriptor of an object borrows an array of fields, which has to
+/// outlive the [`Schema`] built on it; the schema is therefore lent to a
+/// closure instead of being returned. Because each level of nesting needs
+/// its own array, descriptors for nested types still have to be written
+/// out in one place.
+///
+/// # Example
+///
+/// ```
+/// use qjson::{FromJson, Schema};
+///
+/// #[derive(Default)]
+/// struct Point {
+///     x: Option<i64>,
+///     y: Option<i64>,
+/// }
+///
+/// impl<'a> FromJson<'a> for Point {
+///     fn with_schema<R>(&mut self, f: impl FnOnce(Schema<'a, '_>) -> R) -> R {
+///         let mut desc = [("x", (&mut self.x).into()), ("y", (&mut self.y).into())];
+///         f(Schema::Object(&mut desc))
+///     }
+/// }
+///
+/// let mut p = Point::default();
+/// p.with_schema(|desc| qjson::from_str::<_, 1>(r#"{"x":1,"y":2}"#, desc)).unwrap();
+/// assert_eq!((p.x, p.y), (Some(1), Some(2)));
+/// ```
+pub trait FromJson<'a> {
+    /// Call `f` with a descriptor which binds into `self`.
+    fn with_schema<R>(&mut self, f: impl FnOnce(Schema<'a, '_>) -> R) -> R;
+}
+
+// the predicate of `ValidatedStr` is not `Debug`
+impl fmt::Debug for Schema<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Array(x) => f.debug_tuple("Array").field(x).finish(),
+            Self::Bool(x) => f.debug_tuple("Bool").field(x).finish(),
+            Self::Enum { out, variants } => f
+                .debug_struct("Enum")
+                .field("out", out)
+                .field("variants", variants)
+                .finish(),
+            Self::Flags { out, names } => f
+                .debug_struct("Flags")
+                .field("out", out)
+                .field("names", names)
+                .finish(),
+            Self::Float(x) => f.debug_tuple("Float").field(x).finish(),
+            Self::FloatArray(x) => f.debug_tuple("FloatArray").field(x).finish(),
+            Self::I8(x) => f.debug_tuple("I8").field(x).finish(),
+            Self::I16(x) => f.debug_tuple("I16").field(x).finish(),
+            Self::I32(x) => f.debug_tuple("I32").field(x).finish(),
+            Self::IntArray(x) => f.debug_tuple("IntArray").field(x).finish(),
+            Self::IndexedObject(x) => f.debug_tuple("IndexedObject").field(x).finish(),
+            Self::Integer(x) => f.debug_tuple("Integer").field(x).finish(),
+            Self::Micros(x) => f.debug_tuple("Micros").field(x).finish(),
+            Self::Millis(x) => f.debug_tuple("Millis").field(x).finish(),
+            Self::Object(x) => f.debug_tuple("Object").field(x).finish(),
+            Self::ObjectCapture(pairs, count) => f
+                .debug_tuple("ObjectCapture")
+                .field(pairs)
+                .field(count)
+                .finish(),
+            Self::ObjectKeys(x) => f.debug_tuple("ObjectKeys").field(x).finish(),
+            Self::OptionalObject { present, fields } => f
+                .debug_struct("OptionalObject")
+                .field("present", present)
+                .field("fields", fields)
+                .finish(),
+            Self::Raw(x) => f.debug_tuple("Raw").field(x).finish(),
+            Self::Str(x) => f.debug_tuple("Str").field(x).finish(),
+            Self::StrBuf(buf, s) => f.debug_tuple("StrBuf").field(buf).field(s).finish(),
+            Self::StrFixed(x) => f.debug_tuple("StrFixed").field(x).finish(),
+            Self::Tagged { tag, out, arms } => f
+                .debug_struct("Tagged")
+                .field("tag", tag)
+                .field("out", out)
+                .field("arms", arms)
+                .finish(),
+            Self::U8(x) => f.debug_tuple("U8").field(x).finish(),
+            Self::U16(x) => f.debug_tuple("U16").field(x).finish(),
+            Self::U32(x) => f.debug_tuple("U32").field(x).finish(),
+            Self::U64(x) => f.debug_tuple("U64").field(x).finish(),
+            Self::ValidatedStr { out, .. } => f
+                .debug_struct("ValidatedStr")
+                .field("out", out)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl<'a, 'b, F: Fields<'a, 'b>> Clear for Option<F> {
+    fn clear(&mut self) {
+        if let Some(desc) = self {
+            desc.clear();
+        }
+    }
+}
+
+impl<'a, 'b> Fields<'a, 'b> for &mut [(&str, Schema<'a, 'b>)] {
+    fn position(&self, key: &str) -> Option<usize> {
+        self.iter().position(|(k, _)| *k == key)
+    }
+
+    fn field(&mut self, i: usize, _: &str) -> &mut Schema<'a, 'b> {
+        &mut self[i].1
+    }
+
+    fn clear(&mut self) {
+        for (_, val) in self.iter_mut() {
+            val.clear();
+        }
+    }
+}
+
+impl<'a, 'b> Fields<'a, 'b> for &mut [(&[&str], &mut Option<usize>, Schema<'a, 'b>)] {
+    fn position(&self, key: &str) -> Option<usize> {
+        self.iter().position(|(ks, ..)| ks.contains(&key))
+    }
+
+    fn field(&mut self, i: usize, key: &str) -> &mut Schema<'a, 'b> {
+        let (ks, matched, v) = &mut self[i];
+        **matched = ks.iter().position(|k| *k == key);
+        v
+    }
+
+    fn clear(&mut self) {
+        for (_, matched, val) in self.iter_mut() {
+            **matched = None;
+            val.clear();
+        }
+    }
+}
+
+impl Clear for Option<&mut [Schema<'_, '_>]> {
+    fn clear(&mut self) {
+        if let Some(desc) = self {
+            for val in desc.iter_mut() {
+                val.clear();
+            }
+        }
+    }
+}
+
+impl<'a, 'b> Schema<'a, 'b> {
+    /// Renders the values bound to the schema as indented JSON-like text.
+    ///
+    /// This is a debugging aid, not a serializer: unbound fields are shown
+    /// as `null` and strings are written as they appeared in the source.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let (mut a, mut b) = (None, None);
+    /// let mut desc = [("a", qjson::Schema::Integer(&mut a)), ("b", qjson::Schema::Str(&mut b))];
+    /// let schema = qjson::Schema::from(&mut desc);
+    ///
+    /// assert_eq!(format!("{}", schema.display()), "{\n  \"a\": null,\n  \"b\": null\n}");
+    /// ```
+    pub fn display(&self) -> SchemaDisplay<'_, 'a, 'b> {
+        SchemaDisplay(self)
+    }
+
+    /// Clears every field in the schema, as if bound to `null`.
+    pub fn clear(&mut self) {
+        match self {
+            Self::Array(a) => {
+                for v in a.iter_mut() {
+                    v.clear();
+                }
+            }
+            Self::Bool(b) => **b = None,
+            Self::Enum { out, .. } => **out = None,
+            Self::Flags { out, .. } => **out = None,
+            Self::Float(f) => **f = None,
+            Self::FloatArray(a) => a.fill(0.0),
+            Self::I8(i) => **i = None,
+            Self::I16(i) => **i = None,
+            Self::I32(i) => **i = None,
+            Self::IntArray(a) => a.fill(0),
+            Self::IndexedObject(a) => {
+                for v in a.iter_mut() {
+                    v.clear();
+                }
+            }
+            Self::Integer(i) => **i = None,
+            Self::Micros(d) | Self::Millis(d) => **d = None,
+            Self::Object(desc) => desc.clear(),
+            Self::ObjectCapture(_, count) => **count = 0,
+            Self::ObjectKeys(desc) => desc.clear(),
+            Self::OptionalObject { present, fields } => {
+                **present = false;
+                fields.clear();
+            }
+            Self::Raw(s) => **s = None,
+            Self::Str(s) => **s = None,
+            Self::StrBuf(_, s) => **s = None,
+            Self::StrFixed(buf) => buf.fill(0),
+            Self::Tagged { out, arms, .. } => {
+                **out = None;
+                for (_, fields) in arms.iter_mut() {
+                    fields.clear();
+                }
+            }
+            Self::U8(u) => **u = None,
+            Self::U16(u) => **u = None,
+            Self::U32(u) => **u = None,
+            Self::U64(u) => **u = None,
+            Self::ValidatedStr { out, .. } => **out = None,
+        }
+    }
+
+    fn is_scalar(&self) -> bool {
+        !matches!(
+            self,
+            Self::Array(_)
+                | Self::FloatArray(_)
+                | Self::IntArray(_)
+                | Self::Object(_)
+                | Self::ObjectCapture(..)
+                | Self::ObjectKeys(_)
+                | Self::OptionalObject { .. }
+                | Self::IndexedObject(_)
+                | Self::Tagged { .. }
+        )
+    }
+}
+
+/// Finds the schema bound to the innermost member of `frames`.
+fn frame_target<'s, 'a, 'b>(
+    mut desc: Option<&'s mut Schema<'a, 'b>>,
+    frames: &[Frame<'_>],
+) -> Option<&'s mut Schema<'a, 'b>> {
+    for frame in frames {
+        let i = frame.field?;
+        desc = match desc? {
+            Schema::Array(a) => a.get_mut(i),
+            Schema::Object(desc) | Schema::OptionalObject { fields: desc, .. } => {
+                desc.get_mut(i).map(|(_, v)| v)
+            }
+            Schema::ObjectKeys(desc) => desc.get_mut(i).map(|(_, _, v)| v),
+            Schema::IndexedObject(a) => a.get_mut(i),
+            Schema::Tagged {
+                out: Some(arm),
+                arms,
+                ..
+            } => arms.get_mut(*arm)?.1.get_mut(i).map(|(_, v)| v),
+            _ => None,
+        };
+    }
+    desc
+}
+
+// impl SchemaDisplay
+
+impl SchemaDisplay<'_, '_, '_> {
+    fn fmt_value(
+        schema: &Schema<'_, '_>,
+        f: &mut fmt::Formatter<'_>,
+        indent: usize,
+    ) -> fmt::Result {
+        fn opt<T: fmt::Display>(f: &mut fmt::Formatter<'_>, val: &Option<T>) -> fmt::Result {
+            match val {
+                Some(val) => write!(f, "{}", val),
+                None => f.write_str("null"),
+            }
+        }
+
+        match schema {
+            Schema::Array(a) => Self::fmt_collection(f, indent, ('[', ']'), a.iter(), |f, v| {
+                Self::fmt_value(v, f, indent + 1)
+            }),
+            Schema::Bool(b) => opt(f, b),
+            Schema::Enum { out, variants } => {
+                opt(f, &out.and_then(|i| variants.get(i)).map(|v| Quoted(v)))
+            }
+            Schema::Flags { out, .. } => opt(f, out),
+            Schema::Float(n) => opt(f, n),
+            Schema::FloatArray(a) => {
+                Self::fmt_collection(f, indent, ('[', ']'), a.iter(), |f, n| write!(f, "{}", n))
+            }
+            Schema::I8(n) => opt(f, n),
+            Schema::I16(n) => opt(f, n),
+            Schema::I32(n) => opt(f, n),
+            Schema::IntArray(a) => {
+                Self::fmt_collection(f, indent, ('[', ']'), a.iter(), |f, n| write!(f, "{}", n))
+            }
+            Schema::IndexedObject(a) => {
+                let items = a.iter().enumerate();
+                Self::fmt_collection(f, indent, ('{', '}'), items, |f, (i, v)| {
+                    write!(f, "\"{}\": ", i)?;
+                    Self::fmt_value(v, f, indent + 1)
+                })
+            }
+            Schema::Integer(n) => opt(f, n),
+            Schema::Micros(n) | Schema::Millis(n) => opt(f, n),
+            Schema::Object(desc) => {
+                Self::fmt_collection(f, indent, ('{', '}'), desc.iter(), |f, (k, v)| {
+                    write!(f, "\"{}\": ", k)?;
+                    Self::fmt_value(v, f, indent + 1)
+                })
+            }
+            Schema::ObjectCapture(pairs, count) => {
+                let pairs = pairs.iter().take(**count);
+                Self::fmt_collection(f, indent, ('{', '}'), pairs, |f, (k, v)| {
+                    write!(f, "\"{}\": {}", k, v)
+                })
+            }
+            Schema::ObjectKeys(desc) => {
+                Self::fmt_collection(f, indent, ('{', '}'), desc.iter(), |f, (ks, i, v)| {
+                    let k = ks.get(i.unwrap_or(0)).copied().unwrap_or_default();
+                    write!(f, "\"{}\": ", k)?;
+                    Self::fmt_value(v, f, indent + 1)
+                })
+            }
+            Schema::OptionalObject { present: false, .. } => f.write_str("null"),
+            Schema::OptionalObject { fields, .. } => {
+                Self::fmt_collection(f, indent, ('{', '}'), fields.iter(), |f, (k, v)| {
+                    write!(f, "\"{}\": ", k)?;
+                    Self::fmt_value(v, f, indent + 1)
+                })
+            }
+            Schema::Raw(s) => opt(f, s),
+            Schema::Str(s) => opt(f, &s.map(Quoted)),
+            Schema::StrBuf(_, s) => opt(f, &s.map(Quoted)),
+            Schema::StrFixed(buf) => {
+                let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+                opt(f, &core::str::from_utf8(&buf[..len]).ok().map(Quoted))
+            }
+            Schema::Tagged { out: None, .. } => f.write_str("null"),
+            Schema::Tagged {
+                tag,
+                out: Some(i),
+                arms,
+            } => {
+                let Some((name, fields)) = arms.get(*i) else {
+                    return f.write_str("null");
+                };
+                let items = iter::once(None).chain(fields.iter().map(Some));
+                Self::fmt_collection(f, indent, ('{', '}'), items, |f, field| match field {
+                    None => write!(f, "\"{}\": \"{}\"", tag, name),
+                    Some((k, v)) => {
+                        write!(f, "\"{}\": ", k)?;
+                        Self::fmt_value(v, f, indent + 1)
+                    }
+                })
+            }
+            Schema::U8(n) => opt(f, n),
+            Schema::U16(n) => opt(f, n),
+            Schema::U32(n) => opt(f, n),
+            Schema::U64(n) => opt(f, n),
+            Schema::ValidatedStr { out, .. } => opt(f, &out.map(Quoted)),
+        }
+    }
+
+    fn fmt_collection<T>(
+        f: &mut fmt::Formatter<'_>,
+        indent: usize,
+        (open, close): (char, char),
+        mut items: impl Iterator<Item = T>,
+        mut fmt_item: impl FnMut(&mut fmt::Formatter<'_>, T) -> fmt::Result,
+    ) -> fmt::Result {
+        write!(f, "{}", open)?;
+        let Some(first) = items.next() else {
+            return write!(f, "{}", close);
+        };
+
+        write!(f, "\n{:1$}", "", (indent + 1) * 2)?;
+        fmt_item(f, first)?;
+        for item in items {
+            write!(f, ",\n{:1$}", "", (indent + 1) * 2)?;
+            fmt_item(f, item)?;
+        }
+        write!(f, "\n{:1$}{2}", "", indent * 2, close)
+    }
+}
+
+impl fmt::Display for SchemaDisplay<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::fmt_value(self.0, f, 0)
+    }
+}
+
+/// A string written between double quotes.
+struct Quoted<'s>(&'s str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.0)
+    }
+}
+
+// serialization
+
+fn write_value<W: fmt::Write>(w: &mut W, schema: &Schema<'_, '_>) -> fmt::Result {
+    fn opt<W: fmt::Write, T: fmt::Display>(w: &mut W, val: &Option<T>) -> fmt::Result {
+        match val {
+            Some(val) => write!(w, "{}", val),
+            None => write_tok(w, Null),
+        }
+    }
+
+    fn opt_str<W: fmt::Write>(w: &mut W, val: Option<&str>) -> fmt::Result {
+        write_tok(
+            w,
+            val.map_or(Null, |s| Str {
+                text: s,
+                escaped: false,
+            }),
+        )
+    }
+
+    // names are source text, so are already escaped
+    fn name<W: fmt::Write>(w: &mut W, n: &str) -> fmt::Result {
+        write!(w, "\"{}\"", n)
+    }
+
+    fn key<W: fmt::Write>(w: &mut W, k: &str) -> fmt::Result {
+        name(w, k)?;
+        write_tok(w, Colon)
+    }
+
+    match schema {
+        Schema::Array(a) => write_seq(w, BracketL, a.iter(), |w, v| write_value(w, v)),
+        Schema::Bool(b) => opt(w, b),
+        Schema::Enum { out, variants } => match out.and_then(|i| variants.get(i)) {
+            Some(variant) => name(w, variant),
+            None => write_tok(w, Null),
+        },
+        Schema::Flags { out: None, .. } => write_tok(w, Null),
+        Schema::Flags {
+            out: Some(bits),
+            names,
+        } => {
+            let set = names
+                .iter()
+                .filter(|(_, mask)| *mask != 0 && bits & mask == *mask);
+            write_seq(w, BracketL, set, |w, (flag, _)| name(w, flag))
+        }
+        Schema::Float(f) => write_float(w, **f),
+        Schema::FloatArray(a) => write_seq(w, BracketL, a.iter(), |w, f| write_float(w, Some(*f))),
+        Schema::I8(n) => opt(w, n),
+        Schema::I16(n) => opt(w, n),
+        Schema::I32(n) => opt(w, n),
+        Schema::IntArray(a) => write_seq(w, BracketL, a.iter(), |w, n| write!(w, "{}", n)),
+        Schema::IndexedObject(a) => write_seq(w, BraceL, a.iter().enumerate(), |w, (i, v)| {
+            write!(w, "\"{}\":", i)?;
+            write_value(w, v)
+        }),
+        Schema::Integer(n) => opt(w, n),
+        Schema::Micros(n) | Schema::Millis(n) => opt(w, n),
+        Schema::Object(desc)
+        | Schema::OptionalObject {
+            fields: desc,
+            present: true,
+        } => write_seq(w, BraceL, desc.iter(), |w, (k, v)| {
+            key(w, k)?;
+            write_value(w, v)
+        }),
+        Schema::ObjectCapture(pairs, count) => {
+            write_seq(w, BraceL, pairs.iter().take(**count), |w, (k, v)| {
+                key(w, k)?;
+                w.write_str(v)
+            })
+        }
+        Schema::ObjectKeys(desc) => write_seq(w, BraceL, desc.iter(), |w, (ks, i, v)| {
+            key(w, ks.get(i.unwrap_or(0)).copied().unwrap_or_default())?;
+            write_value(w, v)
+        }),
+        Schema::OptionalObject { present: false, .. } => write_tok(w, Null),
+        Schema::Raw(s) => opt(w, s),
+        Schema::Str(s) => opt_str(w, **s),
+        Schema::StrBuf(_, s) => opt_str(w, **s),
+        Schema::StrFixed(buf) => {
+            let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+            opt_str(w, str::from_utf8(&buf[..len]).ok())
+        }
+        Schema::Tagged { tag, out, arms } => {
+            let Some((arm, fields)) = out.and_then(|i| arms.get(i)) else {
+                return write_tok(w, Null);
+            };
+            let items = iter::once(None).chain(fields.iter().map(Some));
+            write_seq(w, BraceL, items, |w, field| match field {
+                None => {
+                    key(w, tag)?;
+                    name(w, arm)
+                }
+                Some((k, v)) => {
+                    key(w, k)?;
+                    write_value(w, v)
+                }
+            })
+        }
+        Schema::U8(n) => opt(w, n),
+        Schema::U16(n) => opt(w, n),
+        Schema::U32(n) => opt(w, n),
+        Schema::U64(n) => opt(w, n),
+        Schema::ValidatedStr { out, .. } => opt_str(w, **out),
+    }
+}
+
+/// Writes the items of an object or array opened by `open`, separated by
+/// commas.
+fn write_seq<W: fmt::Write, T>(
+    w: &mut W,
+    open: Token<'_>,
+    items: impl Iterator<Item = T>,
+    mut write_item: impl FnMut(&mut W, T) -> fmt::Result,
+) -> fmt::Result {
+    write_tok(w, open)?;
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            write_tok(w, Comma)?;
+        }
+        write_item(w, item)?;
+    }
+    write_tok(w, if open == BraceL { BraceR } else { BracketR })
+}
+
+/// Writes the shortest decimal which parses back to the same value, in the
+/// same form as `f64`'s `Debug` (e.g., `1.0`, `1e-7`); non-finite values are
+/// written as `null`.
+fn write_float<W: fmt::Write>(w: &mut W, f: Option<f64>) -> fmt::Result {
+    let f = match f {
+        Some(f) if f.is_finite() => f,
+        _ => return write_tok(w, Null),
+    };
+    if f.is_sign_negative() {
+        w.write_char('-')?;
+    }
+    if f == 0.0 {
+        return w.write_str("0.0");
+    }
+
+    let mut buf = [b'0'; 17];
+    let (len, k) = shortest_digits(f, &mut buf);
+    let mut digits = buf.iter().take(len).map(|d| char::from(*d));
+    // the value is `0.d1d2...` times `10^k`
+    match k {
+        ..=-4 | 17.. => {
+            w.write_char(digits.next().unwrap_or('0'))?;
+            if len > 1 {
+                w.write_char('.')?;
+            }
+            digits.try_for_each(|d| w.write_char(d))?;
+            write!(w, "e{}", k - 1)
+        }
+        ..=0 => {
+            w.write_str("0.")?;
+            let zeros = iter::repeat_n('0', k.unsigned_abs() as usize);
+            zeros.chain(digits).try_for_each(|d| w.write_char(d))
+        }
+        _ => {
+            let int = k as usize;
+            let zeros = iter::repeat_n('0', int.saturating_sub(len));
+            digits
+                .clone()
+                .take(int)
+                .chain(zeros)
+                .try_for_each(|d| w.write_char(d))?;
+            w.write_char('.')?;
+            if len > int {
+                digits.skip(int).try_for_each(|d| w.write_char(d))
+            } else {
+                w.write_char('0')
+            }
+        }
+    }
+}
+
+/// Writes the shortest digits which identify a finite, non-zero float into
+/// `out`, returning how many were written and the decimal exponent `k` of
+/// the value `0.d1d2...` times `10^k`.
+///
+/// This is the free-format algorithm of Steele & White, as refined by
+/// Burger & Dybvig, over fixed-size integers so that it has no panic paths.
+fn shortest_digits(f: f64, out: &mut [u8; 17]) -> (usize, i32) {
+    let bits = f.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i32;
+    let frac = bits & ((1 << 52) - 1);
+    let (m, e) = match biased {
+        0 => (frac, -1074),
+        _ => (frac | 1 << 52, biased - 1075),
+    };
+    // ties between the neighbours round to even when parsed
+    let even = m & 1 == 0;
+    // at a power of two the float below is half as close as the one above
+    let lower = u32::from(frac == 0 && biased > 1);
+
+    // v = r / s, and the midpoints to the neighbours are up / s and down / s
+    let (pos, neg) = (e.max(0) as u32, (-e).max(0) as u32);
+    let mut r = Big::from(m);
+    r.mul_pow2(pos + 1 + lower);
+    let mut s = Big::from(1);
+    s.mul_pow2(neg + 1 + lower);
+    let mut up = Big::from(1);
+    up.mul_pow2(pos + lower);
+    let mut down = Big::from(1);
+    down.mul_pow2(pos);
+
+    // an underestimate of `log10(v)`: `v >= 2^x`, and 78913 / 2^18 is just
+    // below `log10(2)`
+    let x = e + 63 - m.leading_zeros() as i32;
+    let mut k = ((x * 78913) >> 18) - 1;
+    if k >= 0 {
+        s.mul_pow10(k as u32);
+    } else {
+        r.mul_pow10(k.unsigned_abs());
+        up.mul_pow10(k.unsigned_abs());
+        down.mul_pow10(k.unsigned_abs());
+    }
+    let high = |r: &Big, up: &Big, s: &Big| {
+        let mut sum = r.clone();
+        sum.add(up);
+        if even {
+            sum >= *s
+        } else {
+            sum > *s
+        }
+    };
+    while high(&r, &up, &s) {
+        s.mul_small(10);
+        k += 1;
+    }
+
+    let mut len = 0;
+    for slot in out.iter_mut() {
+        r.mul_small(10);
+        up.mul_small(10);
+        down.mul_small(10);
+        let mut d = 0;
+        while d < 9 && r >= s {
+            r.sub(&s);
+            d += 1;
+        }
+        let low = if even { r <= down } else { r < down };
+        let high = high(&r, &up, &s);
+        len += 1;
+        *slot = b'0' + d;
+        match (low, high) {
+            (false, false) => continue,
+            (true, false) => (),
+            (false, true) => *slot += 1,
+            (true, true) => {
+                let mut twice = r.clone();
+                twice.mul_small(2);
+                *slot += u8::from(twice >= s);
+            }
+        }
+        break;
+    }
+    (len, k)
+}
+
+/// A fixed-size unsigned integer, wide enough for the values in
+/// [`shortest_digits`]; the most significant limb is first, so the derived
+/// ordering is numeric.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Big([u32; 40]);
+
+// impl Big
+
+impl Big {
+    fn from(n: u64) -> Self {
+        let mut big = Self([0; 40]);
+        let [.., hi, lo] = &mut big.0;
+        *hi = (n >> 32) as u32;
+        *lo = n as u32;
+        big
+    }
+
+    fn mul_small(&mut self, n: u32) {
+        let mut carry = 0;
+        for limb in self.0.iter_mut().rev() {
+            let x = u64::from(*limb) * u64::from(n) + carry;
+            *limb = x as u32;
+            carry = x >> 32;
+        }
+    }
+
+    fn mul_pow2(&mut self, mut n: u32) {
+        while n >= 31 {
+            self.mul_small(1 << 31);
+            n -= 31;
+        }
+        self.mul_small(1 << n);
+    }
+
+    fn mul_pow10(&mut self, mut n: u32) {
+        while n >= 9 {
+            self.mul_small(1_000_000_000);
+            n -= 9;
+        }
+        self.mul_small(10_u32.pow(n));
+    }
+
+    fn add(&mut self, other: &Self) {
+        let mut carry = false;
+        for (a, b) in self.0.iter_mut().zip(&other.0).rev() {
+            let (x, c1) = a.overflowing_add(*b);
+            let (x, c2) = x.overflowing_add(u32::from(carry));
+            *a = x;
+            carry = c1 || c2;
+        }
+    }
+
+    /// Subtracts a smaller integer.
+    fn sub(&mut self, other: &Self) {
+        let mut borrow = false;
+        for (a, b) in self.0.iter_mut().zip(&other.0).rev() {
+            let (x, b1) = a.overflowing_sub(*b);
+            let (x, b2) = x.overflowing_sub(u32::from(borrow));
+            *a = x;
+            borrow = b1 || b2;
+        }
+    }
+}
+
+fn write_tok<W: fmt::Write>(w: &mut W, tok: Token<'_>) -> fmt::Result {
+    match tok {
+        Bool(b) => w.write_str(if b { "true" } else { "false" }),
+        BraceL => w.write_char('{'),
+        BraceR => w.write_char('}'),
+        BracketL => w.write_char('['),
+        BracketR => w.write_char(']'),
+        Colon => w.write_char(':'),
+        Comma => w.write_char(','),
+        Null => w.write_str("null"),
+        Number(n) => w.write_str(n),
+        Str { text: s, .. } => {
+            w.write_char('"')?;
+            write_escaped(w, s)?;
+            w.write_char('"')
+        }
+    }
+}
+
+/// Writes `s` with the characters which cannot appear in a JSON string
+/// escaped.
+fn write_escaped<W: fmt::Write>(w: &mut W, s: &str) -> fmt::Result {
+    let mut rest = s;
+    while let Some(i) = rest.find(|c: char| matches!(c, '"' | '\\') || c.is_control()) {
+        w.write_str(&rest[..i])?;
+        let c = rest[i..].chars().next().unwrap_or_default();
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\x08' => w.write_str("\\b")?,
+            '\x0C' => w.write_str("\\f")?,
+            c => write!(w, "\\u{:04x}", c as u32)?,
+        }
+        rest = &rest[i + c.len_utf8()..];
+    }
+    w.write_str(rest)
+}
+
+// impl Parser
+
+impl<'a, const D: usize> Parser<'a, D> {
+    fn new(json: &'a str, opts: Options) -> Self {
+        Self::with_tokenizer(Tokenizer::new(StrSource::new(json), opts))
+    }
+}
+
+impl<'a, const D: usize, S: Source<'a>> Parser<'a, D, S> {
+    fn with_tokenizer(tok: Tokenizer<'a, S>) -> Self {
+        Self {
+            tok,
+            peek: None,
+            bound: 0,
+        }
+    }
+
+    fn parse(&mut self, desc: Option<&mut Schema<'a, '_>>) -> Result<(), Error> {
+        if self.tok.opts.allow_empty {
+            match self.tok.next() {
+                Some(tok) => self.peek = Some(tok?),
+                None => return Ok(()),
+            }
+        }
+
+        self.parse_value(desc, 0)?;
+        self.assume_complete()
+    }
+
+    fn validate_all(&mut self, out: &mut [Error]) -> usize
+    where
+        S: Clone,
+    {
+        let mut n = 0;
+
+        // errors are recovered from by replaying the tokens since the last
+        // resync, to find how many collections were left open
+        let mut mark = (self.tok.clone(), 0);
+        let mut is_array = false;
+        let mut res = match self.tok.next() {
+            None if self.tok.opts.allow_empty => return 0,
+            None => Err(self.tok.err(UnexpectedEof)),
+            Some(tok) => tok.and_then(|tok| {
+                is_array = tok == BracketL;
+                self.peek = Some(tok);
+                self.parse_value(None, 0)
+            }),
+        };
+
+        loop {
+            let err = match res {
+                // any trailing tokens are a single error
+                Ok(()) => match self.assume_complete() {
+                    Ok(()) => break,
+                    Err(err) => {
+                        if let Some(slot) = out.get_mut(n) {
+                            *slot = err;
+                            n += 1;
+                        }
+                        break;
+                    }
+                },
+                Err(err) => err,
+            };
+
+            let Some(slot) = out.get_mut(n) else {
+                break;
+            };
+            let (eof, kind) = (err.kind == UnexpectedEof, err.kind);
+            *slot = err;
+            n += 1;
+
+            let mut nesting = self.nesting_since(mark.0.clone(), mark.1);
+            if kind == UnknownIdentifier {
+                // a misspelt literal is cut off by the character which did
+                // not match, so that is the next token if it is one
+                let last = self.tok.src.text(self.tok.prev_pos, self.tok.src.pos());
+                self.peek = match last.chars().next() {
+                    Some(c @ ('{' | '}' | '[' | ']' | ':' | ',')) => self.tok.tok(c).ok(),
+                    _ => None,
+                };
+                match self.peek {
+                    Some(BraceL | BracketL) => nesting += 1,
+                    Some(BraceR | BracketR) => nesting = nesting.saturating_sub(1),
+                    _ => (),
+                }
+            }
+            res = match self.resync(nesting) {
+                Ok(Resync::Member) => {
+                    mark = (self.tok.clone(), 1);
+                    self.parse_root_members(is_array)
+                }
+                Ok(Resync::Closed) => Ok(()),
+                Ok(Resync::Eof) if !eof => Err(self.tok.err(UnexpectedEof)),
+                Ok(Resync::Eof) => break,
+                Err(err) => Err(err),
+            };
+        }
+
+        n
+    }
+
+    /// Counts the collections left open by the tokens read since `mark`,
+    /// given `nesting` were open there.
+    #[cold]
+    fn nesting_since(&self, mut mark: Tokenizer<'a, S>, mut nesting: usize) -> usize {
+        let offset = self.tok.src.offset();
+        while mark.src.offset() < offset {
+            match mark.next() {
+                Some(Ok(BraceL | BracketL)) => nesting += 1,
+                Some(Ok(BraceR | BracketR)) => nesting = nesting.saturating_sub(1),
+                Some(_) => (),
+                None => break,
+            }
+        }
+        nesting
+    }
+
+    /// Skip tokens until the next member of the root value, or its end.
+    ///
+    /// This is conservative: the remainder of a member containing an error
+    /// is not checked. `nesting` counts every token read so far, including
+    /// any which has been peeked.
+    fn resync(&mut self, mut nesting: usize) -> Result<Resync, Error> {
+        if self.peek.take() == Some(Comma) && nesting == 1 {
+            return Ok(Resync::Member);
+        }
+        while nesting > 0 {
+            match self.tok.next() {
+                Some(tok) => match tok? {
+                    Comma if nesting == 1 => return Ok(Resync::Member),
+                    BraceL | BracketL => nesting += 1,
+                    BraceR | BracketR => nesting -= 1,
+                    _ => (),
+                },
+                None => return Ok(Resync::Eof),
+            }
+        }
+        Ok(Resync::Closed)
+    }
+
+    fn parse_root_members(&mut self, is_array: bool) -> Result<(), Error> {
+        let end = if is_array { BracketR } else { BraceR };
+        loop {
+            if is_array {
+                self.parse_value(None, usize::from(self.tok.opts.count_arrays))?;
+            } else {
+                self.assume_tok_str()?;
+                self.assume_tok_kind(Colon)?;
+                self.parse_value(None, 1)?;
+            }
+            if self.end_of_collection(end)? {
+                return Ok(());
+            }
+        }
+    }
+
+    fn peek_kind(&mut self) -> Result<ValueKind, Error> {
+        match *self.peek_next_tok()? {
+            BraceL => Ok(ValueKind::Object),
+            BracketL => Ok(ValueKind::Array),
+            Bool(_) => Ok(ValueKind::Bool),
+            Null => Ok(ValueKind::Null),
+            Number(_) => Ok(ValueKind::Number),
+            Str { .. } => Ok(ValueKind::Str),
+            BraceR | BracketR | Colon | Comma => Err(self.tok.err(UnexpectedToken)),
+        }
+    }
+
+    fn root_keys(&mut self, out: &mut [&'a str]) -> Result<usize, Error> {
+        if self.peek_kind()? != ValueKind::Object {
+            return Err(self.tok.err(MismatchedTypes));
+        }
+        self.next_tok()?;
+        if D == 0 {
+            return Err(self.depth_exceeded(1));
+        }
+
+        let mut n = 0;
+        if !self.advance_if_close(BraceR)? {
+            loop {
+                let key = self.assume_tok_str()?;
+                *out.get_mut(n)
+                    .ok_or_else(|| self.tok.err(InsufficientArrayLength))? = key;
+                n += 1;
+
+                self.assume_tok_kind(Colon)?;
+                self.parse_value(None, 1)?;
+                if self.end_of_collection(BraceR)? {
+                    break;
+                }
+            }
+        }
+
+        self.assume_complete()?;
+        Ok(n)
+    }
+
+    fn parse_stack(
+        &mut self,
+        mut root: Option<&mut Schema<'a, '_>>,
+        stack: &mut [Frame<'a>],
+    ) -> Result<(), Error> {
+        if self.tok.opts.allow_empty {
+            match self.tok.next() {
+                Some(tok) => self.peek = Some(tok?),
+                None => return Ok(()),
+            }
+        }
+
+        let mut depth = 0;
+
+        loop {
+            // start of a value
+            let tok = self.next_tok()?;
+            let start = self.tok.start_pos;
+            let desc = frame_target(root.as_deref_mut(), &stack[..depth]);
+            let opened = match (tok, desc) {
+                (BraceL, Some(Schema::Raw(_))) => Some(false),
+                (BracketL, Some(Schema::Raw(_))) => Some(true),
+                (tok, Some(Schema::Raw(out))) => {
+                    self.parse_scalar(tok, None)?;
+                    let raw = self.tok.text_from(start);
+                    self.bind(out, raw);
+                    None
+                }
+                (
+                    BraceL,
+                    None
+                    | Some(Schema::IndexedObject(_) | Schema::Object(_) | Schema::ObjectKeys(_)),
+                ) => Some(false),
+                (BraceL, Some(Schema::ObjectCapture(_, count))) => {
+                    **count = 0;
+                    Some(false)
+                }
+                (BraceL, Some(Schema::OptionalObject { present, .. })) => {
+                    **present = true;
+                    Some(false)
+                }
+                (BraceL, Some(Schema::Tagged { tag, out, arms })) => {
+                    let arm = self.find_tag(tag, arms)?;
+                    self.bind(out, arm);
+                    Some(false)
+                }
+                (BracketL, None | Some(Schema::Array(_))) => Some(true),
+                (BracketL, Some(Schema::Flags { out, names })) => {
+                    let bits = self.parse_flags(names)?;
+                    self.bind(out, bits);
+                    None
+                }
+                (BracketL, Some(Schema::FloatArray(a))) => {
+                    self.parse_num_array(a, Self::parse_float)?;
+                    None
+                }
+                (BracketL, Some(Schema::IntArray(a))) => {
+                    self.parse_num_array(a, Self::parse_integer)?;
+                    None
+                }
+                (tok, desc) => {
+                    self.parse_scalar(tok, desc)?;
+                    None
+                }
+            };
+
+            if let Some(array) = opened {
+                let frame = stack
+                    .get_mut(depth)
+                    .ok_or_else(|| self.depth_exceeded_by(depth + 1, true))?;
+                *frame = Frame {
+                    array,
+                    start,
+                    ..Frame::new()
+                };
+                depth += 1;
+
+                if self.advance_if_close(if array { BracketR } else { BraceR })? {
+                    match frame_target(root.as_deref_mut(), &stack[..depth - 1]) {
+                        Some(Schema::Raw(out)) => {
+                            let raw = self.tok.text_from(start);
+                            self.bind(out, raw);
+                        }
+                        // present, but empty
+                        Some(Schema::OptionalObject { fields, .. }) => fields.clear(),
+                        Some(desc) => desc.clear(),
+                        None => (),
+                    }
+                    depth -= 1;
+                } else {
+                    self.begin_member(root.as_deref_mut(), &mut stack[..depth])?;
+                    continue;
+                }
+            }
+
+            // end of a value; close every collection it completes
+            loop {
+                let Some(frame) = depth.checked_sub(1).map(|i| stack[i]) else {
+                    return self.assume_complete();
+                };
+
+                if let Some(Schema::ObjectCapture(pairs, count)) =
+                    frame_target(root.as_deref_mut(), &stack[..depth - 1])
+                {
+                    // as in `parse_obj_capture`, nothing is peeked here
+                    let raw = self.tok.text_from(frame.start);
+                    pairs[frame.member] = (frame.key, raw.trim_start());
+                    **count = frame.member + 1;
+                    self.bound += 1;
+                }
+
+                let close = if frame.array { BracketR } else { BraceR };
+                if self.end_of_collection(close)? {
+                    depth -= 1;
+                    if let Some(Schema::Raw(out)) =
+                        frame_target(root.as_deref_mut(), &stack[..depth])
+                    {
+                        // a raw collection's frame starts at its opening token
+                        let raw = self.tok.text_from(frame.start);
+                        self.bind(out, raw);
+                    }
+                } else {
+                    stack[depth - 1].member += 1;
+                    self.begin_member(root.as_deref_mut(), &mut stack[..depth])?;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Reads up to the value of the next member of the innermost frame, and
+    /// finds the schema it binds to.
+    fn begin_member(
+        &mut self,
+        root: Option<&mut Schema<'a, '_>>,
+        stack: &mut [Frame<'a>],
+    ) -> Result<(), Error> {
+        let (frame, parents) = stack.split_last_mut().unwrap();
+        self.check_container_len(frame.member + 1)?;
+        let desc = frame_target(root, parents);
+
+        if frame.array {
+            frame.field = match desc {
+                Some(Schema::Array(a)) if frame.member < a.len() => Some(frame.member),
+                Some(Schema::Raw(_)) | None => None,
+                Some(_) => return Err(self.tok.err(InsufficientArrayLength)),
+            };
+            return Ok(());
+        }
+
+        let key = self.assume_tok_str()?;
+        self.assume_tok_kind(Colon)?;
+
+        frame.field = match desc {
+            Some(Schema::Object(desc) | Schema::OptionalObject { fields: desc, .. }) => {
+                self.first_key(&mut frame.seen, desc.position(key), |k| desc.position(k))
+            }
+            Some(Schema::Tagged {
+                out: Some(arm),
+                arms,
+                ..
+            }) => match arms.get(*arm) {
+                Some((_, desc)) => {
+                    self.first_key(&mut frame.seen, desc.position(key), |k| desc.position(k))
+                }
+                None => None,
+            },
+            Some(Schema::ObjectKeys(desc)) => {
+                let i = self.first_key(&mut frame.seen, desc.position(key), |k| desc.position(k));
+                i.inspect(|i| {
+                    desc.field(*i, key);
+                })
+            }
+            Some(Schema::IndexedObject(a)) => {
+                let i = self.key_index(key, a.len())?;
+                self.first_key(&mut frame.seen, Some(i), |k| {
+                    self.key_index(k, a.len()).ok()
+                })
+            }
+            Some(Schema::ObjectCapture(pairs, _)) => {
+                if frame.member >= pairs.len() {
+                    return Err(self.tok.err(InsufficientArrayLength));
+                }
+                frame.key = key;
+                frame.start = self.tok.src.pos();
+                None
+            }
+            _ => None,
+        };
+
+        Ok(())
+    }
+
+    fn parse_value(
+        &mut self,
+        mut desc: Option<&mut Schema<'a, '_>>,
+        depth: usize,
+    ) -> Result<(), Error> {
+        let arrays = usize::from(self.tok.opts.count_arrays);
+
+        if let Some(Schema::Raw(out)) = desc.as_deref_mut() {
+            // nothing is peeked after a value, so it ends where the
+            // tokenizer stopped
+            self.peek_next_tok()?;
+            let start = self.tok.start_pos;
+            self.parse_value(None, depth)?;
+            let raw = self.tok.text_from(start);
+            self.bind(out, raw);
+            return Ok(());
+        }
+
+        match (self.next_tok()?, desc) {
+            (BraceL, Some(Schema::Object(v))) => {
+                self.parse_obj(Some(&mut **v), depth + 1)?;
+            }
+            (BraceL, Some(Schema::ObjectCapture(pairs, count))) => {
+                self.parse_obj_capture(pairs, count, depth + 1)?;
+            }
+            (BraceL, Some(Schema::ObjectKeys(v))) => {
+                self.parse_obj(Some(&mut **v), depth + 1)?;
+            }
+            (BraceL, Some(Schema::OptionalObject { present, fields })) => {
+                **present = true;
+                self.parse_obj(Some(&mut **fields), depth + 1)?;
+            }
+            (BraceL, Some(Schema::IndexedObject(a))) => {
+                self.parse_indexed_obj(a, depth + 1)?;
+            }
+            (BraceL, Some(Schema::Tagged { tag, out, arms })) => {
+                let arm = self.find_tag(tag, arms)?;
+                self.bind(out, arm);
+                self.parse_obj(Some(&mut *arms[arm].1), depth + 1)?;
+            }
+            (BraceL, None) => self.parse_obj(None::<&mut [(&str, Schema)]>, depth + 1)?,
+
+            (BracketL, _) if arrays > 0 && depth >= D => {
+                return Err(self.depth_exceeded(depth + 1));
+            }
+            (BracketL, Some(Schema::Array(a))) => self.parse_array(Some(a), depth + arrays)?,
+            (BracketL, None) => self.parse_array(None, depth + arrays)?,
+            (BracketL, Some(Schema::Flags { out, names })) => {
+                let bits = self.parse_flags(names)?;
+                self.bind(out, bits);
+            }
+            (BracketL, Some(Schema::FloatArray(a))) => {
+                self.parse_num_array(a, Self::parse_float)?
+            }
+            (BracketL, Some(Schema::IntArray(a))) => {
+                self.parse_num_array(a, Self::parse_integer)?
+            }
+
+            (tok, desc) => self.parse_scalar(tok, desc)?,
+        }
+        Ok(())
+    }
+
+    /// Binds a value which is a single token (i.e., not an object or array).
+    fn parse_scalar(
+        &mut self,
+        tok: Token<'a>,
+        desc: Option<&mut Schema<'a, '_>>,
+    ) -> Result<(), Error> {
+        match (tok, desc) {
+            (Bool(b), Some(Schema::Bool(v))) => self.bind(v, b),
+            (Str { text: s, .. }, Some(Schema::Bool(v))) if self.tok.opts.lenient => {
+                let b = s.parse().map_err(|_| self.tok.err(MismatchedTypes))?;
+                self.bind(v, b);
+            }
+            (Bool(_), None) => (),
+
+            (Number(n), Some(Schema::Float(v))) => {
+                let f = self.parse_float(n)?;
+                self.bind(v, f);
+            }
+            (Number(n), Some(Schema::I8(v))) => self.bind_integer(v, n)?,
+            (Number(n), Some(Schema::I16(v))) => self.bind_integer(v, n)?,
+            (Number(n), Some(Schema::I32(v))) => self.bind_integer(v, n)?,
+            (Number(n), Some(Schema::Integer(v))) => self.bind_integer(v, n)?,
+            (Number(n), Some(Schema::U8(v))) => self.bind_integer(v, n)?,
+            (Number(n), Some(Schema::U16(v))) => self.bind_integer(v, n)?,
+            (Number(n), Some(Schema::U32(v))) => self.bind_integer(v, n)?,
+            (Number(n), Some(Schema::U64(v))) => self.bind_integer(v, n)?,
+            (Number(n), Some(Schema::Micros(v) | Schema::Millis(v))) => self.bind_integer(v, n)?,
+            (Number(_), None) => (),
+
+            (Null, Some(v)) if self.tok.opts.null_keeps_default && v.is_scalar() => (),
+            (Null, Some(v)) => v.clear(),
+            (Null, None) => (),
+
+            (Str { escaped: true, .. }, Some(Schema::Str(_) | Schema::ValidatedStr { .. })) => {
+                return Err(self.tok.err_at_start(EscapeInBorrowedString));
+            }
+            (
+                Str {
+                    text: s,
+                    escaped: false,
+                },
+                Some(Schema::Str(v)),
+            ) => self.bind(v, s),
+            (Str { text: s, .. }, Some(Schema::StrBuf(buf, v))) => {
+                let len = unescape(s, buf, self.tok.opts.lenient).map_err(|k| self.tok.err(k))?;
+                let (decoded, rest) = mem::take(buf).split_at_mut(len);
+                *buf = rest;
+                // `unescape` only writes whole characters
+                self.bind(v, str::from_utf8(decoded).unwrap_or_default());
+            }
+            (Str { text: s, .. }, Some(Schema::StrFixed(buf))) => {
+                let lenient = self.tok.opts.lenient;
+                // measured first so a failure leaves the buffer untouched
+                let len = unescaped_len(s, lenient).map_err(|k| self.tok.err(k))?;
+                if len > buf.len() {
+                    return Err(self.tok.err(InsufficientBuffer));
+                }
+                let (decoded, rest) = buf.split_at_mut(len);
+                unescape(s, decoded, lenient).map_err(|k| self.tok.err(k))?;
+                rest.fill(0);
+                self.bound += 1;
+            }
+            (Str { text: s, .. }, Some(Schema::ValidatedStr { out, valid })) => {
+                if !valid(s) {
+                    return Err(self.tok.err_at_start(ValidationFailed));
+                }
+                self.bind(out, s);
+            }
+            (Str { text: s, .. }, Some(Schema::Enum { out, variants })) => {
+                match variants.iter().position(|v| *v == s) {
+                    Some(i) => self.bind(out, i),
+                    None => return Err(self.tok.err(UnknownVariant)),
+                }
+            }
+            (Str { .. }, None) => (),
+
+            (BraceR, _) | (BracketR, _) | (Comma, _) | (Colon, _) => {
+                return Err(self.tok.err(UnexpectedToken));
+            }
+
+            _ => return Err(self.tok.err(MismatchedTypes)),
+        }
+        Ok(())
     }
-}
 
-impl<'a, 'b> From<&'b mut [(&'b str, Schema<'a, 'b>)]> for Schema<'a, 'b> {
-    fn from(desc: &'b mut [(&'b str, Schema<'a, 'b>)]) -> Self {
-        Self::Object(desc)
+    // FIXME: Significant performance hit using `libcore` conversions here
+    // FIXME: `f64` parsing from `libcore` has panic paths
+    fn parse_float(&self, n: &str) -> Result<f64, Error> {
+        n.parse().map_err(|_| self.tok.err(InvalidNumber))
     }
-}
 
-impl<'a, 'b, const N: usize> From<&'b mut [(&'b str, Schema<'a, 'b>); N]> for Schema<'a, 'b> {
-    fn from(desc: &'b mut [(&'b str, Schema<'a, 'b>); N]) -> Self {
-        Self::Object(&mut desc[..])
+    /// Parse an integer and bind it, checking it fits in the target width.
+    ///
+    /// The tokenizer has already checked the number grammar, so conversion
+    /// can only fail if the value is out of range.
+    fn bind_integer<T: FromStr>(&mut self, v: &mut Option<T>, n: &str) -> Result<(), Error> {
+        let i = self.parse_integer(n)?;
+        self.bind(v, i);
+        Ok(())
     }
-}
 
-impl<'a, 'b> From<&'b mut Option<&'a str>> for Schema<'a, 'b> {
-    fn from(desc: &'b mut Option<&'a str>) -> Self {
-        Self::Str(desc)
+    fn parse_integer<T: FromStr>(&self, n: &str) -> Result<T, Error> {
+        if !is_integer(n) {
+            return Err(self.tok.err(MismatchedTypes));
+        }
+        // point at the number, as the digits themselves are well formed
+        n.parse()
+            .map_err(|_| self.tok.err_at_start(NumberOutOfRange))
     }
-}
 
-impl Clear for Option<&mut [(&str, Schema<'_, '_>)]> {
-    fn clear(&mut self) {
-        if let Some(desc) = self {
-            for (_, val) in desc.iter_mut() {
-                val.clear();
-            }
-        }
+    fn bind<T>(&mut self, v: &mut Option<T>, val: T) {
+        *v = Some(val);
+        self.bound += 1;
     }
-}
 
-impl Clear for Option<&mut [Schema<'_, '_>]> {
-    fn clear(&mut self) {
-        if let Some(desc) = self {
-            for val in desc.iter_mut() {
-                val.clear();
-            }
-        }
+    /// Builds a `MaxDepthExceeded` error for a collection opened at `depth`.
+    ///
+    /// The rest of the collection is skipped to find how deep it goes, so
+    /// the error can report the limit the input actually needed.
+    #[cold]
+    fn depth_exceeded(&mut self, depth: usize) -> Error {
+        self.depth_exceeded_by(depth, self.tok.opts.count_arrays)
     }
-}
 
-impl Schema<'_, '_> {
-    fn clear(&mut self) {
-        match self {
-            Self::Array(a) => {
-                for v in a.iter_mut() {
-                    v.clear();
+    /// Like [`Parser::depth_exceeded`], but for a collection of either kind,
+    /// optionally counting arrays as a level of nesting.
+    #[cold]
+    fn depth_exceeded_by(&mut self, depth: usize, arrays: bool) -> Error {
+        let mut err = self.tok.err(MaxDepthExceeded);
+        let mut open = 1;
+        let mut reached = depth;
+
+        while open > 0 {
+            match self.next_tok() {
+                Ok(BraceL) => {
+                    open += 1;
+                    reached = reached.max(depth + open - 1);
                 }
-            }
-            Self::Bool(b) => **b = None,
-            Self::Float(f) => **f = None,
-            Self::Integer(i) => **i = None,
-            Self::Object(desc) => {
-                for (_, v) in desc.iter_mut() {
-                    v.clear();
+                Ok(BracketL) if arrays => {
+                    open += 1;
+                    reached = reached.max(depth + open - 1);
                 }
+                Ok(BraceR) => open -= 1,
+                Ok(BracketR) if arrays => open -= 1,
+                Ok(_) => (),
+                // report what was seen before the input went bad
+                Err(_) => break,
             }
-            Self::Str(s) => **s = None,
         }
+
+        err.depth = Some(reached);
+        err
     }
-}
 
-// impl Parser
+    fn parse_obj<'b>(
+        &mut self,
+        mut obj: Option<impl Fields<'a, 'b>>,
+        depth: usize,
+    ) -> Result<(), Error>
+    where
+        'a: 'b,
+    {
+        if depth > D {
+            return Err(self.depth_exceeded(depth));
+        }
 
-impl<'a, const D: usize> Parser<'a, D> {
-    fn new(json: &'a str) -> Self {
-        Self {
-            tok: Tokenizer::new(json),
-            peek: None,
+        if self.advance_if_close(BraceR)? {
+            obj.clear();
+        } else {
+            let mut len = 0;
+            let mut seen = Seen::default();
+            loop {
+                len += 1;
+                self.check_container_len(len)?;
+                let field = self.assume_tok_str()?;
+                self.assume_tok_kind(Colon)?;
+                let val = match obj.as_mut() {
+                    Some(desc) => self
+                        .first_key(&mut seen, desc.position(field), |k| desc.position(k))
+                        .map(|i| desc.field(i, field)),
+                    None => None,
+                };
+
+                self.parse_value(val, depth)?;
+                if self.end_of_collection(BraceR)? {
+                    break;
+                }
+            }
         }
+
+        Ok(())
     }
 
-    fn parse(&mut self, desc: Option<&mut Schema<'a, '_>>) -> Result<(), Error> {
-        self.parse_value(desc, 0)?;
-        self.assume_complete()
+    /// Finds the arm of a tagged object selected by its `tag` member,
+    /// without consuming any of the object.
+    ///
+    /// The object has just been opened. Its members are scanned ahead with
+    /// a copy of the tokenizer; any malformed input is left for the parse
+    /// which follows to report.
+    fn find_tag(
+        &self,
+        tag: &str,
+        arms: &[(&str, &mut [(&str, Schema<'a, '_>)])],
+    ) -> Result<usize, Error> {
+        let Some(mut tok) = self.tok.fork() else {
+            return Err(self.tok.err(MismatchedTypes));
+        };
+        let mut nesting = 0_usize;
+        let mut is_key = true;
+
+        while let Some(Ok(next)) = tok.next() {
+            match (nesting, next) {
+                (0, BraceR | BracketR) => break,
+                (0, Str { text: key, .. }) if is_key && key == tag => {
+                    if tok.next().and_then(Result::ok) != Some(Colon) {
+                        break;
+                    }
+                    return match tok.next() {
+                        Some(Ok(Str { text: name, .. })) => arms
+                            .iter()
+                            .position(|(arm, _)| *arm == name)
+                            .ok_or_else(|| tok.err_at_start(UnknownIdentifier)),
+                        Some(Ok(_)) => Err(tok.err_at_start(MismatchedTypes)),
+                        _ => break,
+                    };
+                }
+                (0, Comma) => is_key = true,
+                (0, Colon) => is_key = false,
+                (_, BraceL | BracketL) => nesting += 1,
+                (_, BraceR | BracketR) => nesting -= 1,
+                _ => (),
+            }
+        }
+
+        Err(tok.err_at_start(UnknownIdentifier))
     }
 
-    fn parse_value(
+    fn parse_indexed_obj(
         &mut self,
-        desc: Option<&mut Schema<'a, '_>>,
+        slots: &mut [Schema<'a, '_>],
         depth: usize,
     ) -> Result<(), Error> {
-        match (self.next_tok()?, desc) {
-            (BraceL, Some(Schema::Object(v))) => {
-                self.parse_obj(Some(*v), depth + 1)?;
-            }
-            (BraceL, None) => self.parse_obj(None, depth + 1)?,
-
-            (BracketL, Some(Schema::Array(a))) => self.parse_array(Some(a), depth)?,
-            (BracketL, None) => self.parse_array(None, depth)?,
-
-            (Bool(b), Some(Schema::Bool(v))) => **v = Some(b),
-            (Bool(_), None) => (),
+        if depth > D {
+            return Err(self.depth_exceeded(depth));
+        }
 
-            (Float(f), Some(Schema::Float(v))) => **v = Some(f),
-            (Float(_), None) => (),
+        if self.advance_if_close(BraceR)? {
+            for v in slots.iter_mut() {
+                v.clear();
+            }
+        } else {
+            let mut len = 0;
+            let mut seen = Seen::default();
+            loop {
+                len += 1;
+                self.check_container_len(len)?;
+                let key = self.assume_tok_str()?;
+                let i = self.key_index(key, slots.len())?;
+                self.assume_tok_kind(Colon)?;
+                let i = self.first_key(&mut seen, Some(i), |k| self.key_index(k, slots.len()).ok());
+                let val = i.map(|i| &mut slots[i]);
 
-            (Integer(i), Some(Schema::Float(v))) => **v = Some(i as f64),
-            (Integer(i), Some(Schema::Integer(v))) => **v = Some(i),
-            (Integer(_), None) => (),
+                self.parse_value(val, depth)?;
+                if self.end_of_collection(BraceR)? {
+                    break;
+                }
+            }
+        }
 
-            (Null, Some(v)) => v.clear(),
-            (Null, None) => (),
+        Ok(())
+    }
 
-            (Str(s), Some(Schema::Str(v))) => **v = Some(s),
-            (Str(_), None) => (),
+    /// Skips field `i` of an object if it has already been bound, when
+    /// [`Options::first_key_wins`] is set.
+    ///
+    /// `index` maps keys to fields, and is used to find fields past the first
+    /// 64 among the keys already read.
+    fn first_key(
+        &self,
+        seen: &mut Seen,
+        i: Option<usize>,
+        index: impl Fn(&str) -> Option<usize>,
+    ) -> Option<usize> {
+        if !self.tok.opts.first_key_wins {
+            return i;
+        }
+        let first = seen.first;
+        if first.is_none() {
+            seen.first = Some((i, self.tok.src.pos()));
+        }
+        let i = i?;
+        let dup = match u32::try_from(i).ok().and_then(|i| 1_u64.checked_shl(i)) {
+            Some(bit) => {
+                let dup = seen.bits & bit != 0;
+                seen.bits |= bit;
+                dup
+            }
+            None => match first {
+                Some((first, start)) => first == Some(i) || self.count_keys(start, i, index) > 1,
+                None => false,
+            },
+        };
+        (!dup).then_some(i)
+    }
 
-            (BraceR, _) | (BracketR, _) | (Comma, _) | (Colon, _) => {
-                return Err(self.tok.err(UnexpectedToken));
+    /// Counts the members of the current object whose keys are field `i`,
+    /// from text position `start` up to the most recently read colon.
+    fn count_keys(&self, start: usize, i: usize, index: impl Fn(&str) -> Option<usize>) -> usize {
+        let text = self.tok.src.text(start, self.tok.src.pos());
+        let toks = Tokenizer::new(StrSource::new(text), self.tok.opts).map_while(Result::ok);
+        let (mut nesting, mut key, mut n) = (0_usize, None, 0);
+        for tok in toks {
+            match tok {
+                BraceL | BracketL => nesting += 1,
+                BraceR | BracketR => nesting = nesting.saturating_sub(1),
+                Str { text, .. } if nesting == 0 => key = Some(text),
+                Colon if nesting == 0 && key.and_then(&index) == Some(i) => n += 1,
+                _ => (),
             }
+        }
+        n
+    }
 
-            _ => return Err(self.tok.err(MismatchedTypes)),
+    /// Parses the key of a [`Schema::IndexedObject`] as an index into a
+    /// slice of `len` schemas.
+    fn key_index(&self, key: &str, len: usize) -> Result<usize, Error> {
+        if key.starts_with('-') || !is_valid_number(key) || !is_integer(key) {
+            return Err(self.tok.err(MismatchedTypes));
         }
-        Ok(())
+        key.parse()
+            .ok()
+            .filter(|i| *i < len)
+            .ok_or_else(|| self.tok.err(InsufficientArrayLength))
     }
 
-    fn parse_obj(
+    fn parse_obj_capture(
         &mut self,
-        mut obj: Option<&mut [(&str, Schema<'a, '_>)]>,
+        pairs: &mut [(&'a str, &'a str)],
+        count: &mut usize,
         depth: usize,
     ) -> Result<(), Error> {
         if depth > D {
-            return Err(self.tok.err(MaxDepthExceeded));
+            return Err(self.depth_exceeded(depth));
         }
 
-        if self.advance_if_tok(BraceR)? {
-            obj.clear();
-        } else {
+        let mut n = 0;
+        *count = 0;
+        if !self.advance_if_close(BraceR)? {
             loop {
-                let field = self.assume_tok_str()?;
+                self.check_container_len(n + 1)?;
+                let key = self.assume_tok_str()?;
+                let pair = pairs
+                    .get_mut(n)
+                    .ok_or_else(|| self.tok.err(InsufficientArrayLength))?;
                 self.assume_tok_kind(Colon)?;
-                let val = obj.as_mut().and_then(|desc| {
-                    desc.iter_mut()
-                        .find_map(|(k, v)| Some(v).filter(|_| *k == field))
-                });
 
-                self.parse_value(val, depth)?;
+                // no token is peeked after the colon or the value, so the
+                // value is the source consumed by the tokenizer in between
+                let start = self.tok.src.pos();
+                self.parse_value(None, depth)?;
+                debug_assert!(self.peek.is_none());
+
+                *pair = (key, self.tok.text_from(start).trim_start());
+                n += 1;
+                // keep the count current so pairs survive a later error
+                *count = n;
+                self.bound += 1;
+
                 if self.end_of_collection(BraceR)? {
                     break;
                 }
@@ -379,12 +3273,13 @@ impl<'a, const D: usize> Parser<'a, D> {
         mut arr: Option<&mut [Schema<'a, '_>]>,
         depth: usize,
     ) -> Result<(), Error> {
-        if self.advance_if_tok(BracketR)? {
+        if self.advance_if_close(BracketR)? {
             arr.clear();
             Ok(())
         } else {
             let mut i = 0;
             loop {
+                self.check_container_len(i + 1)?;
                 let val = arr
                     .as_mut()
                     .map(|desc| {
@@ -403,8 +3298,74 @@ impl<'a, const D: usize> Parser<'a, D> {
         }
     }
 
+    /// Parses an array of numbers into `out`, which it must exactly fill.
+    fn parse_num_array<T>(
+        &mut self,
+        out: &mut [T],
+        parse: impl Fn(&Self, &str) -> Result<T, Error>,
+    ) -> Result<(), Error> {
+        let mut i = 0;
+        if !self.advance_if_close(BracketR)? {
+            loop {
+                self.check_container_len(i + 1)?;
+                let n = match self.next_tok()? {
+                    Number(n) => parse(self, n)?,
+                    BraceR | BracketR | Comma | Colon => return Err(self.tok.err(UnexpectedToken)),
+                    _ => return Err(self.tok.err(MismatchedTypes)),
+                };
+                let slot = out
+                    .get_mut(i)
+                    .ok_or_else(|| self.tok.err(InsufficientArrayLength))?;
+                *slot = n;
+                self.bound += 1;
+                i += 1;
+
+                if self.end_of_collection(BracketR)? {
+                    break;
+                }
+            }
+        }
+
+        if i != out.len() {
+            return Err(self.tok.err(InsufficientArrayLength));
+        }
+        Ok(())
+    }
+
+    fn parse_flags(&mut self, names: &[(&str, u64)]) -> Result<u64, Error> {
+        let mut bits = 0;
+        if self.advance_if_close(BracketR)? {
+            return Ok(bits);
+        }
+        let mut len = 0;
+        loop {
+            len += 1;
+            self.check_container_len(len)?;
+            match self.next_tok()? {
+                Str { text: s, .. } => match names.iter().find(|(name, _)| *name == s) {
+                    Some((_, b)) => bits |= b,
+                    None => return Err(self.tok.err(UnknownVariant)),
+                },
+                BraceR | BracketR | Comma | Colon => return Err(self.tok.err(UnexpectedToken)),
+                _ => return Err(self.tok.err(MismatchedTypes)),
+            }
+            if self.end_of_collection(BracketR)? {
+                return Ok(bits);
+            }
+        }
+    }
+
+    /// Checks that a collection may have `len` members; see
+    /// [`Options::max_container_len`].
+    fn check_container_len(&self, len: usize) -> Result<(), Error> {
+        match self.tok.opts.max_container_len {
+            Some(max) if len > max => Err(self.tok.err(ContainerTooLarge)),
+            _ => Ok(()),
+        }
+    }
+
     fn end_of_collection(&mut self, with: Token<'a>) -> Result<bool, Error> {
-        match (self.advance_if_tok(Comma)?, self.advance_if_tok(with)?) {
+        match (self.advance_if_tok(Comma)?, self.advance_if_close(with)?) {
             (false, true) => Ok(true),
             (true, false) => Ok(false),
             (true, true) => Err(self.tok.err(UnexpectedTrailingComma)),
@@ -412,6 +3373,20 @@ impl<'a, const D: usize> Parser<'a, D> {
         }
     }
 
+    /// Consume the closing token `with`, if it is next.
+    ///
+    /// A collection closed by the other kind of bracket (e.g. `[1}`) fails
+    /// with `MismatchedBracket` at the offending token.
+    fn advance_if_close(&mut self, with: Token<'a>) -> Result<bool, Error> {
+        if self.advance_if_tok(with)? {
+            Ok(true)
+        } else if matches!(self.peek, Some(BraceR | BracketR)) {
+            Err(self.tok.err(MismatchedBracket))
+        } else {
+            Ok(false)
+        }
+    }
+
     fn assume_tok_kind(&mut self, tok: Token<'_>) -> Result<(), Error> {
         if self.next_tok()? != tok {
             return Err(self.tok.err(UnexpectedToken));
@@ -421,7 +3396,7 @@ impl<'a, const D: usize> Parser<'a, D> {
 
     fn assume_tok_str(&mut self) -> Result<&'a str, Error> {
         match self.next_tok()? {
-            Str(s) => Ok(s),
+            Str { text: s, .. } => Ok(s),
             _ => Err(self.tok.err(UnexpectedToken)),
         }
     }
@@ -475,33 +3450,204 @@ impl<'a, const D: usize> Parser<'a, D> {
     }
 }
 
-// impl Tokenizer
+// impl Tokens
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<(Token<'a>, Span), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.tok.next()? {
+            Ok(tok) => {
+                let span = Span {
+                    lineno: self.tok.start.0,
+                    col: self.tok.start.1,
+                    start: self.tok.start_offset,
+                    end: self.tok.src.offset(),
+                };
+                Some(Ok((tok, span)))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// impl StrSource
 
-impl<'a> Tokenizer<'a> {
+impl<'a> StrSource<'a> {
     fn new(json: &'a str) -> Self {
         Self {
+            json,
+            chars: json.chars(),
+        }
+    }
+}
+
+impl<'a> Source<'a> for StrSource<'a> {
+    #[inline]
+    fn next_char(&mut self) -> Option<char> {
+        self.chars.next()
+    }
+
+    #[inline]
+    fn peek_char(&mut self) -> Option<char> {
+        self.chars.as_str().chars().next()
+    }
+
+    #[inline]
+    fn offset(&self) -> usize {
+        self.json.len() - self.chars.as_str().len()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.offset()
+    }
+
+    #[inline]
+    fn skip(&mut self) {}
+
+    fn text(&self, start: usize, end: usize) -> &'a str {
+        #[cfg(debug_assertions)]
+        return &self.json[start..end];
+        #[cfg(not(debug_assertions))]
+        // Safety: positions are only taken between characters of the source
+        unsafe {
+            self.json.get_unchecked(start..end)
+        }
+    }
+
+    fn fork(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+// impl CharSource
+
+impl<'a, I: Iterator<Item = char>> CharSource<'a, I> {
+    fn new(chars: I, buf: &'a mut [u8]) -> Self {
+        Self {
+            chars,
+            peeked: None,
+            offset: 0,
+            buf: buf.as_mut_ptr(),
+            cap: buf.len(),
+            pos: 0,
+            last: None,
+            overflow: None,
+            _buf: PhantomData,
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = char>> Source<'a> for CharSource<'a, I> {
+    fn next_char(&mut self) -> Option<char> {
+        // the text is missing a character, so stop reading
+        if self.overflow.is_some() {
+            return None;
+        }
+
+        let c = self.peeked.take().or_else(|| self.chars.next())?;
+        let len = c.len_utf8();
+        if self.cap - self.pos >= len {
+            // Safety: the bytes from `pos` are in bounds, and are not part
+            // of any text handed out yet
+            let dst = unsafe { core::slice::from_raw_parts_mut(self.buf.add(self.pos), len) };
+            c.encode_utf8(dst);
+            self.pos += len;
+            self.last = Some(len);
+        } else {
+            // whitespace is skipped without being written, so only fail
+            // once another character is read
+            self.overflow = Some(self.offset);
+            self.last = None;
+        }
+        self.offset += len;
+        Some(c)
+    }
+
+    fn peek_char(&mut self) -> Option<char> {
+        if self.peeked.is_none() && self.overflow.is_none() {
+            self.peeked = self.chars.next();
+        }
+        self.peeked
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn skip(&mut self) {
+        match self.last.take() {
+            Some(len) => self.pos -= len,
+            None => self.overflow = None,
+        }
+    }
+
+    fn text(&self, start: usize, end: usize) -> &'a str {
+        debug_assert!(start <= end && end <= self.pos);
+        // Safety: the bytes before `pos` were written one encoded `char` at
+        // a time, positions are only taken between them, and they are not
+        // written to again
+        unsafe {
+            let bytes = core::slice::from_raw_parts(self.buf.add(start), end - start);
+            str::from_utf8_unchecked(bytes)
+        }
+    }
+
+    fn fork(&self) -> Option<Self> {
+        None
+    }
+}
+
+// impl Tokenizer
+
+impl<'a, S: Source<'a>> Tokenizer<'a, S> {
+    fn new(src: S, opts: Options) -> Self {
+        Self {
+            opts,
+            roundtrip: false,
             lineno: 1,
             col: 0,
-            chars: json.chars(),
-            prev: json,
+            start: (1, 0),
+            start_offset: 0,
+            start_pos: 0,
+            src,
+            prev: 0,
+            prev_pos: 0,
+            _src: PhantomData,
         }
     }
 
-    /// The source string *not* including the most recently popped char.
-    fn as_str(&self) -> &'a str {
-        self.chars.as_str()
+    /// A copy of the tokenizer which can be read ahead, if there is one.
+    fn fork(&self) -> Option<Self> {
+        Some(Self {
+            src: self.src.fork()?,
+            ..*self
+        })
     }
 
-    /// The source string *including* the most recently popped char.
-    fn as_str_prev(&self) -> &'a str {
-        self.prev
+    /// The text from `start` up to the most recently popped char.
+    fn text_from(&self, start: usize) -> &'a str {
+        self.src.text(start, self.src.pos())
     }
 
     /// Pop the next character from the source iterator if one exists.
     #[inline]
     fn next_char(&mut self) -> Option<char> {
-        self.prev = self.chars.as_str();
-        let c = self.chars.next()?;
+        self.prev = self.src.offset();
+        self.prev_pos = self.src.pos();
+        let c = self.src.next_char()?;
         self.col += 1;
         Some(c)
     }
@@ -512,6 +3658,42 @@ impl<'a> Tokenizer<'a> {
             lineno: self.lineno,
             col: self.col,
             kind,
+            depth: None,
+            offset: self.prev,
+        }
+    }
+
+    /// Builds an error pointing at the start of the last token.
+    fn err_at_start(&self, kind: ErrorKind) -> Error {
+        Error {
+            lineno: self.start.0,
+            col: self.start.1,
+            kind,
+            depth: None,
+            offset: self.start_offset,
+        }
+    }
+
+    /// Tokenize from the first character of a token.
+    fn tok(&mut self, c: char) -> Result<Token<'a>, Error> {
+        match c {
+            '{' => Ok(BraceL),
+            '}' => Ok(BraceR),
+            '[' => Ok(BracketL),
+            ']' => Ok(BracketR),
+            ':' => Ok(Colon),
+            ',' => Ok(Comma),
+
+            't' => self.tok_chars(&['r', 'u', 'e'], Bool(true)),
+            'f' => self.tok_chars(&['a', 'l', 's', 'e'], Bool(false)),
+            'n' => self.tok_chars(&['u', 'l', 'l'], Null),
+
+            '"' => self.tok_string('"'),
+            '\'' if self.opts.lenient => self.tok_string('\''),
+
+            '0'..='9' | '-' => self.tok_number(),
+
+            _ => Err(self.err(UnknownStartOfToken)),
         }
     }
 
@@ -525,109 +3707,81 @@ impl<'a> Tokenizer<'a> {
         Ok(tok)
     }
 
-    /// Attempt to emit a string token.
-    fn tok_string(&mut self) -> Result<Token<'a>, Error> {
-        let s = self.as_str();
+    /// Attempt to emit a string token delimited by `quote`.
+    fn tok_string(&mut self, quote: char) -> Result<Token<'a>, Error> {
+        let start = self.src.pos();
 
-        let mut len = 0;
         let mut escape = false;
+        let mut escaped = false;
 
         loop {
-            match self
+            let c = self
                 .next_char()
-                .ok_or_else(|| self.err(UnterminatedString))?
-            {
-                '"' if !escape => break,
-                '\\' => escape = true,
+                .ok_or_else(|| self.err(UnterminatedString))?;
+            match c {
+                c if c == quote && !escape => break,
+                '\\' => {
+                    escape = !escape;
+                    escaped = true;
+                }
                 c if c.is_control() => return Err(self.err(UnexpectedControlCharacterInString)),
                 _ => escape = false,
             }
-            len += 1;
         }
 
-        #[cfg(debug_assertions)]
-        let s = &s[..len];
-        #[cfg(not(debug_assertions))]
-        // Safety: We know `len` is within the length of `s`
-        let s = unsafe { s.get_unchecked(..len) };
-
-        Ok(Str(s))
+        // the text ends before the closing quote
+        let text = self.src.text(start, self.prev_pos);
+        Ok(Str { text, escaped })
     }
 
     /// Attempt to emit a numeric (*either* integer or float) token.
+    ///
+    /// The number is only checked against the JSON grammar here; it is not
+    /// converted until it is bound to a schema.
     fn tok_number(&mut self) -> Result<Token<'a>, Error> {
-        let s = self.as_str_prev();
+        // The iterator impl already advanced past the first character
+        while let Some('0'..='9' | '.' | '-' | '+' | 'e' | 'E') = self.src.peek_char() {
+            self.next_char();
+        }
 
-        let mut len = 0_usize;
-        let mut float = false;
+        let n = self.text_from(self.start_pos);
 
-        let mut cs = s.chars();
-        while let Some(c) = cs.next() {
-            if !matches!(c, '0'..='9' | '.' | '-') {
-                break;
-            }
-            len += 1;
+        if !is_valid_number(n) {
+            return Err(self.err(InvalidNumber));
         }
 
-        // The iterator impl already advanced past the first character
-        let advance_by = len.saturating_sub(1);
-        for i in 0..advance_by {
-            if self.next_char().ok_or_else(|| self.err(UnexpectedEof))? == '.' {
-                float = true;
-                // Floats ending with periods parse, but are not valid JSON
-                if i == advance_by {
-                    return Err(self.err(InvalidNumber));
-                }
-            }
+        if self.roundtrip && !roundtrips(n) {
+            return Err(self.err(PrecisionLoss));
         }
 
-        #[cfg(debug_assertions)]
-        let n = &s[..len];
-        #[cfg(not(debug_assertions))]
-        // Safety: We know `len` is within the length of `s`
-        let n = unsafe { s.get_unchecked(..len) };
-
-        // FIXME: Significant performance hit using `libcore` conversions here
-        // FIXME: `f64` parsing from `libcore` has panic paths
-        if float {
-            let f: f64 = n.parse().map_err(|_| self.err(InvalidNumber))?;
-            Ok(Float(f))
-        } else {
-            let i: i64 = n.parse().map_err(|_| self.err(InvalidNumber))?;
-            Ok(Integer(i))
-        }
+        Ok(Number(n))
     }
 }
 
-impl<'a> Iterator for Tokenizer<'a> {
+impl<'a, S: Source<'a>> Iterator for Tokenizer<'a, S> {
     type Item = Result<Token<'a>, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match self.next_char()? {
+            let c = self.next_char()?;
+            match c {
                 ' ' | '\t' | '\r' => (),
                 '\n' => {
                     self.lineno += 1;
                     self.col = 0;
                 }
-
-                '{' => return Some(Ok(BraceL)),
-                '}' => return Some(Ok(BraceR)),
-                '[' => return Some(Ok(BracketL)),
-                ']' => return Some(Ok(BracketR)),
-                ':' => return Some(Ok(Colon)),
-                ',' => return Some(Ok(Comma)),
-
-                't' => return Some(self.tok_chars(&['r', 'u', 'e'], Bool(true))),
-                'f' => return Some(self.tok_chars(&['a', 'l', 's', 'e'], Bool(false))),
-                'n' => return Some(self.tok_chars(&['u', 'l', 'l'], Null)),
-
-                '"' => return Some(self.tok_string()),
-
-                '0'..='9' | '-' => return Some(self.tok_number()),
-
-                _ => return Some(Err(self.err(UnknownStartOfToken))),
+                '\x0B' | '\x0C' if self.opts.lenient => (),
+                c if self.opts.lenient && self.opts.whitespace.contains(&c) => (),
+                _ => {
+                    self.start = (self.lineno, self.col);
+                    self.start_offset = self.prev;
+                    self.start_pos = self.prev_pos;
+
+                    return Some(self.tok(c));
+                }
             }
+            // whitespace is not part of any token
+            self.src.skip();
         }
     }
 }
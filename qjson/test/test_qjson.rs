@@ -43,7 +43,7 @@ fn ok_empty_arr_with_whitespace() {
 #[test]
 fn err_empty_arr_extra_opening_brace() {
     let src = r#"[[]"#;
-    let err = qjson::validate::<1>(src).unwrap_err();
+    let err = qjson::validate::<2>(src).unwrap_err();
     assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedEof);
     assert_eq!(err.lineno(), 1);
     assert_eq!(err.col(), 3);
@@ -127,6 +127,25 @@ fn err_shallow_depth() {
     assert_eq!(err.kind(), qjson::ErrorKind::MaxDepthExceeded);
     assert_eq!(err.lineno(), 1);
     assert_eq!(err.col(), 6);
+    assert_eq!(err.depth(), Some(2));
+}
+
+#[test]
+fn err_col_counts_chars() {
+    let err = qjson::validate::<1>("{\"é😀\":tru}").unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnknownIdentifier);
+    assert_eq!(err.lineno(), 1);
+    assert_eq!(err.col(), 10);
+
+    let err = qjson::validate::<1>("[\"😀\",\n \"ü\" 1]").unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MissingComma);
+    assert_eq!(err.lineno(), 2);
+    assert_eq!(err.col(), 6);
+
+    let mut s: Option<&str> = None;
+    let err = qjson::from_str_pos::<_, 0>("\n😀", &mut s).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnknownStartOfToken);
+    assert_eq!((err.lineno(), err.col()), (2, 1));
 }
 
 #[test]
@@ -136,6 +155,72 @@ fn err_deep_depth() {
     assert_eq!(err.kind(), qjson::ErrorKind::MaxDepthExceeded);
     assert_eq!(err.lineno(), 1);
     assert_eq!(err.col(), 51);
+    assert_eq!(err.depth(), Some(12));
+}
+
+#[test]
+fn err_deep_array_depth() {
+    let src = "[[[[[[[[[[[[]]]]]]]]]]]]";
+    let err = qjson::validate::<10>(src).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MaxDepthExceeded);
+    assert_eq!(err.lineno(), 1);
+    assert_eq!(err.col(), 11);
+    assert_eq!(err.depth(), Some(12));
+}
+
+#[test]
+fn err_depth_reached() {
+    let src = r#"{"a":{"b":[{"c":{}}],"d":{}},"e":{"f":{}}}"#;
+    let err = qjson::validate::<1>(src).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MaxDepthExceeded);
+    assert_eq!(err.depth(), Some(5));
+    assert!(qjson::validate::<4>(src).is_err());
+    assert!(qjson::validate::<5>(src).is_ok());
+
+    let err = qjson::validate::<1>("[1,2").unwrap_err();
+    assert_eq!(err.depth(), None);
+}
+
+#[test]
+fn ok_count_arrays_depth() {
+    qjson::validate::<3>(r#"[{"a":[1]}]"#).unwrap();
+    qjson::validate::<1>("[1,[],2]").unwrap_err();
+    qjson::validate::<2>("[1,[],2]").unwrap();
+    qjson::validate::<0>("1").unwrap();
+
+    let mut a = [0; 2];
+    let mut desc = [("a", qjson::Schema::IntArray(&mut a))];
+    qjson::from_str::<_, 2>(r#"{"a":[1,2]}"#, &mut desc).unwrap();
+
+    // opting out only counts objects
+    let mut opts = qjson::Options::new();
+    opts.count_arrays = false;
+    qjson::validate_with::<1>(r#"[{"a":[1]}]"#, opts).unwrap();
+    qjson::validate_with::<0>("[1,[],2]", opts).unwrap();
+}
+
+#[test]
+fn err_count_arrays_depth() {
+    let src = format!("{}{}", "[".repeat(100), "]".repeat(100));
+
+    let err = qjson::validate::<16>(&src).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MaxDepthExceeded);
+    assert_eq!(err.col(), 17);
+    assert_eq!(err.depth(), Some(100));
+
+    // the nesting of arrays is unbounded when opting out
+    let mut opts = qjson::Options::new();
+    opts.count_arrays = false;
+    qjson::validate_with::<0>(&src, opts).unwrap();
+
+    let err = qjson::validate::<1>(r#"{"a":[{}]}"#).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MaxDepthExceeded);
+    assert_eq!(err.depth(), Some(3));
+
+    let mut a = [0; 2];
+    let mut desc = [("a", qjson::Schema::IntArray(&mut a))];
+    let err = qjson::from_str::<_, 1>(r#"{"a":[1,2]}"#, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MaxDepthExceeded);
 }
 
 #[test]
@@ -240,7 +325,7 @@ fn ok_array_of_integers() {
     ];
     let mut desc = [("arr", qjson::Schema::Array(&mut arr_desc))];
 
-    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    qjson::from_str::<_, 2>(src, &mut desc).unwrap();
     assert_eq!(s.arr, [Some(1), Some(-1), None]);
 }
 
@@ -259,11 +344,29 @@ fn ok_array_of_different_types() {
     let mut arr_desc = [qjson::Schema::Integer(a0), qjson::Schema::Float(a1)];
     let mut desc = [("arr", qjson::Schema::Array(&mut arr_desc))];
 
-    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    qjson::from_str::<_, 2>(src, &mut desc).unwrap();
     assert_eq!(s.a0.unwrap(), 1);
     assert!((s.a1.unwrap() - 1.0).abs() < 1e-9);
 }
 
+#[test]
+fn ok_tuple_schema() {
+    let (mut a, mut b, mut c, mut d) = (None, None, None, None::<u8>);
+    {
+        let mut desc = qjson::tuple_schema!(&mut a, &mut b, &mut c, &mut d);
+        qjson::from_str::<_, 1>(r#"[1, 1.5, "x", 255]"#, &mut desc).unwrap();
+    }
+    assert_eq!(a, Some(1));
+    assert_eq!(b, Some(1.5));
+    assert_eq!(c, Some("x"));
+    assert_eq!(d, Some(255));
+
+    let (mut a, mut b) = (None::<i64>, None::<bool>);
+    let mut desc = qjson::tuple_schema!(&mut a, &mut b);
+    let err = qjson::from_str::<_, 1>("[1, 2]", &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+}
+
 #[test]
 fn ok_array_of_objects() {
     #[derive(Default)]
@@ -298,7 +401,7 @@ fn ok_array_of_objects() {
     ];
     let mut desc = [("arr", qjson::Schema::Array(&mut arr_desc))];
 
-    qjson::from_str::<_, 2>(src, &mut desc).unwrap();
+    qjson::from_str::<_, 3>(src, &mut desc).unwrap();
     assert_eq!(s.obj0_name.unwrap(), "foo");
     assert_eq!(s.obj0_val.unwrap(), 1);
     assert_eq!(s.obj1_name.unwrap(), "bar");
@@ -352,3 +455,2389 @@ fn err_neg_inf() {
     assert_eq!(err.lineno(), 1);
     assert_eq!(err.col(), 6);
 }
+
+#[test]
+fn ok_lenient_single_quoted_str() {
+    let mut s = None;
+    let src = r#"{'s':'it "works"'}"#;
+    let mut desc = [("s", qjson::Schema::Str(&mut s))];
+    let mut opts = qjson::Options::new();
+    opts.lenient = true;
+    qjson::from_str_with::<_, 1>(src, &mut desc, opts).unwrap();
+    assert_eq!(s.unwrap(), r#"it "works""#);
+}
+
+#[test]
+fn ok_double_quoted_str_with_single_quote() {
+    let mut s = None;
+    let src = r#"{"s":"it's"}"#;
+    let mut desc = [("s", qjson::Schema::Str(&mut s))];
+    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    assert_eq!(s.unwrap(), "it's");
+}
+
+#[test]
+fn err_strict_single_quoted_str() {
+    let src = r#"{'s':'v'}"#;
+    let err = qjson::validate::<1>(src).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnknownStartOfToken);
+    assert_eq!(err.lineno(), 1);
+    assert_eq!(err.col(), 2);
+}
+
+#[test]
+fn ok_lenient_quoted_bool() {
+    let (mut a, mut b) = (None, None);
+    let src = r#"{"a":"true","b":'false'}"#;
+    let mut desc = [
+        ("a", qjson::Schema::Bool(&mut a)),
+        ("b", qjson::Schema::Bool(&mut b)),
+    ];
+    let mut opts = qjson::Options::new();
+    opts.lenient = true;
+    qjson::from_str_with::<_, 1>(src, &mut desc, opts).unwrap();
+    assert_eq!((a, b), (Some(true), Some(false)));
+}
+
+#[test]
+fn err_quoted_bool() {
+    let mut b: Option<bool> = None;
+    let err = qjson::from_str::<_, 0>(r#""true""#, &mut b).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+
+    let mut opts = qjson::Options::new();
+    opts.lenient = true;
+    let err = qjson::from_str_with::<_, 0>(r#""True""#, &mut b, opts).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+    assert_eq!(b, None);
+}
+
+#[test]
+fn err_lenient_single_quoted_str_control_character() {
+    let src = "'\n'";
+    let mut opts = qjson::Options::new();
+    opts.lenient = true;
+    let err = qjson::validate_with::<0>(src, opts).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        qjson::ErrorKind::UnexpectedControlCharacterInString
+    );
+}
+
+#[test]
+fn ok_lenient_whitespace() {
+    let src = "{\x0B\"a\":\x0C1,\x0B\"b\":;2}";
+    let mut opts = qjson::Options::new();
+    opts.lenient = true;
+    opts.whitespace = &[';'];
+    qjson::validate_with::<1>(src, opts).unwrap();
+}
+
+#[test]
+fn err_strict_whitespace() {
+    let err = qjson::validate::<1>("[\x0B1]").unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnknownStartOfToken);
+
+    let mut opts = qjson::Options::new();
+    opts.whitespace = &[';'];
+    let err = qjson::validate_with::<1>("[;1]", opts).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnknownStartOfToken);
+}
+
+#[test]
+fn err_empty_input() {
+    let err = qjson::validate::<1>("").unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedEof);
+    let err = qjson::validate::<1>(" \r\n\t").unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn ok_allow_empty_input() {
+    let mut opts = qjson::Options::new();
+    opts.allow_empty = true;
+    qjson::validate_with::<1>("", opts).unwrap();
+    qjson::validate_with::<1>(" \r\n\t", opts).unwrap();
+}
+
+#[test]
+fn ok_allow_empty_input_stack() {
+    let mut opts = qjson::Options::new();
+    opts.allow_empty = true;
+    let mut stack = [qjson::Frame::new(); 1];
+    qjson::validate_stack_with("", &mut stack, opts).unwrap();
+    qjson::validate_stack_with(" \r\n\t", &mut stack, opts).unwrap();
+    qjson::validate_stack_with("[1]", &mut stack, opts).unwrap();
+
+    let mut i = Some(1);
+    let mut desc = [("i", qjson::Schema::Integer(&mut i))];
+    qjson::from_str_stack_with(" ", &mut desc, &mut stack, opts).unwrap();
+    assert_eq!(i, Some(1));
+
+    let err = qjson::validate_stack("", &mut stack).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn ok_allow_empty_input_leaves_schema_untouched() {
+    let mut i = Some(1);
+    let mut desc = [("i", qjson::Schema::Integer(&mut i))];
+    let mut opts = qjson::Options::new();
+    opts.allow_empty = true;
+    qjson::from_str_with::<_, 1>("  ", &mut desc, opts).unwrap();
+    assert_eq!(i, Some(1));
+}
+
+#[test]
+fn ok_allow_empty_non_empty_input() {
+    let mut i = None;
+    let mut desc = [("i", qjson::Schema::Integer(&mut i))];
+    let mut opts = qjson::Options::new();
+    opts.allow_empty = true;
+    qjson::from_str_with::<_, 1>(r#" {"i":1}"#, &mut desc, opts).unwrap();
+    assert_eq!(i, Some(1));
+    let err = qjson::validate_with::<1>(" }", opts).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedToken);
+}
+
+#[test]
+fn ok_schema_macro() {
+    let src = r#"{"arr":[{"name":"foo","val":1},[2.0]],"flag":true}"#;
+
+    let mut name = None;
+    let mut val = None;
+    let mut f = None;
+    let mut flag = None;
+
+    let mut desc = qjson::schema!({
+        "arr": [
+            { "name": Str(&mut name), "val": Integer(&mut val) },
+            [Float(&mut f)],
+        ],
+        "flag": Bool(&mut flag),
+    });
+
+    qjson::from_str::<_, 3>(src, &mut desc).unwrap();
+    assert_eq!(name, Some("foo"));
+    assert_eq!(val, Some(1));
+    assert!((f.unwrap() - 2.0).abs() < 1e-9);
+    assert_eq!(flag, Some(true));
+}
+
+#[test]
+fn ok_schema_macro_array_root() {
+    let mut i0 = None;
+    let mut i1 = None;
+    let mut desc = qjson::schema!([Integer(&mut i0), Integer(&mut i1)]);
+    qjson::from_str::<_, 1>("[1,2]", &mut desc).unwrap();
+    assert_eq!((i0, i1), (Some(1), Some(2)));
+}
+
+#[test]
+fn ok_peek_kind() {
+    use qjson::ValueKind;
+
+    assert_eq!(qjson::peek_kind::<1>("{}").unwrap(), ValueKind::Object);
+    assert_eq!(qjson::peek_kind::<1>("\n [1").unwrap(), ValueKind::Array);
+    assert_eq!(qjson::peek_kind::<1>(r#""s""#).unwrap(), ValueKind::Str);
+    assert_eq!(qjson::peek_kind::<1>("-1").unwrap(), ValueKind::Number);
+    assert_eq!(qjson::peek_kind::<1>("1.5").unwrap(), ValueKind::Number);
+    assert_eq!(qjson::peek_kind::<1>("false").unwrap(), ValueKind::Bool);
+    assert_eq!(qjson::peek_kind::<1>("null").unwrap(), ValueKind::Null);
+}
+
+#[test]
+fn err_peek_kind() {
+    let err = qjson::peek_kind::<1>("").unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedEof);
+    let err = qjson::peek_kind::<1>(" ]").unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedToken);
+    assert_eq!(err.col(), 2);
+}
+
+#[test]
+fn ok_str_multibyte() {
+    let mut a = None;
+    let (mut buf, mut b) = ([0; 8], None);
+    let src = r#"{"a":"smile 😀!","b":"é\"ü"}"#;
+    {
+        let mut desc = [
+            ("a", (&mut a).into()),
+            ("b", qjson::Schema::StrBuf(&mut buf, &mut b)),
+        ];
+        qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    }
+    assert_eq!(a, Some("smile 😀!"));
+    assert_eq!(b, Some("é\"ü"));
+}
+
+#[test]
+fn ok_str_escaped_backslash() {
+    let (mut buf, mut s) = ([0; 4], None);
+    {
+        let mut desc = [qjson::Schema::StrBuf(&mut buf, &mut s)];
+        qjson::from_str::<_, 1>(r#"["a\\"]"#, &mut desc).unwrap();
+    }
+    assert_eq!(s, Some("a\\"));
+}
+
+#[test]
+fn err_str_escape_in_borrowed_string() {
+    for (src, col) in [
+        (r#"{"s":"a\nb"}"#, 6),
+        (r#"{"n":1, "s": "\\"}"#, 14),
+        (r#"{"s":"\u00e9"}"#, 6),
+    ] {
+        let mut s = None;
+        let mut desc = [("s", qjson::Schema::Str(&mut s))];
+        let err = qjson::from_str::<_, 1>(src, &mut desc).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            qjson::ErrorKind::EscapeInBorrowedString,
+            "{src}"
+        );
+        assert_eq!((err.lineno(), err.col()), (1, col), "{src}");
+    }
+
+    // unbound strings are not decoded, so their escapes are fine
+    qjson::validate::<1>(r#"["a\nb"]"#).unwrap();
+}
+
+#[test]
+fn ok_str_buf() {
+    let (mut buf, mut name_buf) = ([0; 32], [0; 8]);
+    let (mut motd, mut name) = (None, None);
+    {
+        let mut desc = [
+            ("motd", qjson::Schema::StrBuf(&mut buf, &mut motd)),
+            ("name", qjson::Schema::StrBuf(&mut name_buf, &mut name)),
+        ];
+        let src = r#"{"motd": "café\n\t\"\\\/\b\f\r 😀", "name": "bob"}"#;
+        qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    }
+    assert_eq!(motd, Some("café\n\t\"\\/\x08\x0C\r 😀"));
+    assert_eq!(name, Some("bob"));
+}
+
+#[test]
+fn ok_str_buf_shares_buffer() {
+    let mut buf = [0; 4];
+    let (mut a, mut b) = (None, None);
+    {
+        let (head, tail) = buf.split_at_mut(2);
+        let mut desc = [
+            qjson::Schema::StrBuf(head, &mut a),
+            qjson::Schema::StrBuf(tail, &mut b),
+        ];
+        qjson::from_str::<_, 1>(r#"["x", "yz"]"#, &mut desc).unwrap();
+    }
+    assert_eq!((a, b), (Some("x"), Some("yz")));
+
+    // duplicate keys each consume the front of the buffer
+    let mut buf = [0; 4];
+    let mut s = None;
+    {
+        let mut desc = [("s", qjson::Schema::StrBuf(&mut buf, &mut s))];
+        qjson::from_str::<_, 1>(r#"{"s": "ab", "s": "cd"}"#, &mut desc).unwrap();
+    }
+    assert_eq!(s, Some("cd"));
+}
+
+#[test]
+fn ok_str_buf_lenient_quote() {
+    let mut buf = [0; 8];
+    let mut s = None;
+    {
+        let mut desc = [qjson::Schema::StrBuf(&mut buf, &mut s)];
+        let opts = qjson::Options {
+            lenient: true,
+            ..Default::default()
+        };
+        qjson::from_str_with::<_, 1>(r"['it\'s']", &mut desc, opts).unwrap();
+    }
+    assert_eq!(s, Some("it's"));
+}
+
+#[test]
+fn err_str_buf_invalid_escape() {
+    for src in [
+        r#"["\x"]"#,
+        r#"["\u00g0"]"#,
+        r#"["\u12"]"#,
+        r#"["\ud83d"]"#,
+        r#"["\ud83dA"]"#,
+        r#"["\ude00"]"#,
+        r#"["\'"]"#,
+    ] {
+        let mut buf = [0; 8];
+        let mut s = None;
+        let mut desc = [qjson::Schema::StrBuf(&mut buf, &mut s)];
+        let err = qjson::from_str::<_, 1>(src, &mut desc).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::InvalidEscape, "{}", src);
+    }
+}
+
+#[test]
+fn err_str_buf_insufficient_buffer() {
+    let mut buf = [0; 4];
+    let mut s = None;
+    let mut desc = [qjson::Schema::StrBuf(&mut buf, &mut s)];
+    let err = qjson::from_str::<_, 1>(r#"["café"]"#, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::InsufficientBuffer);
+}
+
+fn is_ident(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+#[test]
+fn ok_validated_str() {
+    let mut host = None;
+    {
+        let mut valid = is_ident;
+        let mut desc = [(
+            "host",
+            qjson::Schema::ValidatedStr {
+                out: &mut host,
+                valid: &mut valid,
+            },
+        )];
+        assert!(format!("{:?}", desc).contains("ValidatedStr"));
+        qjson::from_str::<_, 1>(r#"{"host": "node_1"}"#, &mut desc).unwrap();
+    }
+    assert_eq!(host, Some("node_1"));
+}
+
+#[test]
+fn err_validated_str() {
+    let mut host = None;
+    let mut calls = 0;
+    let err = {
+        let mut valid = |s: &str| {
+            calls += 1;
+            is_ident(s)
+        };
+        let mut desc = [(
+            "host",
+            qjson::Schema::ValidatedStr {
+                out: &mut host,
+                valid: &mut valid,
+            },
+        )];
+        let src = "{\n  \"host\": \"no spaces\"\n}";
+        qjson::from_str::<_, 1>(src, &mut desc).unwrap_err()
+    };
+    assert_eq!(err.kind(), qjson::ErrorKind::ValidationFailed);
+    assert_eq!((err.lineno(), err.col()), (2, 11));
+    assert_eq!(calls, 1);
+    assert!(host.is_none());
+
+    // escapes cannot be borrowed, so are rejected before validating
+    let err = {
+        let mut valid = |_: &str| {
+            calls += 1;
+            true
+        };
+        let mut desc = [(
+            "host",
+            qjson::Schema::ValidatedStr {
+                out: &mut host,
+                valid: &mut valid,
+            },
+        )];
+        qjson::from_str::<_, 1>(r#"{"host": "a\"b"}"#, &mut desc).unwrap_err()
+    };
+    assert_eq!(err.kind(), qjson::ErrorKind::EscapeInBorrowedString);
+    assert_eq!((err.lineno(), err.col()), (1, 10));
+    assert_eq!(calls, 1);
+    assert!(host.is_none());
+}
+
+#[test]
+fn ok_str_fixed() {
+    let mut name = [0xff; 8];
+    let src = r#"{"name":"foo"}"#;
+    let mut desc = [("name", qjson::Schema::StrFixed(&mut name))];
+    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    assert_eq!(&name, b"foo\0\0\0\0\0");
+}
+
+#[test]
+fn ok_str_fixed_escapes() {
+    let mut name = [0xff; 8];
+    let src = r#"{"name":"a\"\u00e9\n"}"#;
+    let mut desc = [("name", qjson::Schema::StrFixed(&mut name))];
+    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    assert_eq!(&name, b"a\"\xc3\xa9\n\0\0\0");
+
+    // the decoded text fits even though the source does not
+    let mut name = [0; 2];
+    let mut desc = [("name", qjson::Schema::StrFixed(&mut name))];
+    qjson::from_str::<_, 1>(r#"{"name":"\u00e9"}"#, &mut desc).unwrap();
+    assert_eq!(&name, "é".as_bytes());
+}
+
+#[test]
+fn ok_str_fixed_exact_length() {
+    let mut name = [0; 3];
+    let src = r#"{"name":"foo"}"#;
+    let mut desc = [("name", qjson::Schema::StrFixed(&mut name))];
+    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    assert_eq!(&name, b"foo");
+}
+
+#[test]
+fn ok_str_fixed_null() {
+    let mut name = *b"foo";
+    let src = r#"{"name":null}"#;
+    let mut desc = [("name", qjson::Schema::StrFixed(&mut name))];
+    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    assert_eq!(&name, b"\0\0\0");
+}
+
+#[test]
+fn err_str_fixed_insufficient_buffer() {
+    let mut name = [0; 2];
+    let src = r#"{"name":"foo"}"#;
+    let mut desc = [("name", qjson::Schema::StrFixed(&mut name))];
+    let err = qjson::from_str::<_, 1>(src, &mut desc).unwrap_err();
+    assert_eq!(&name, b"\0\0");
+    assert_eq!(err.kind(), qjson::ErrorKind::InsufficientBuffer);
+    assert_eq!(err.lineno(), 1);
+    assert_eq!(err.col(), 13);
+
+    let mut name = *b"abc";
+    let src = r#"{"name":"\t\t\t\t"}"#;
+    let mut desc = [("name", qjson::Schema::StrFixed(&mut name))];
+    let err = qjson::from_str::<_, 1>(src, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::InsufficientBuffer);
+    assert_eq!(&name, b"abc");
+}
+
+#[test]
+fn ok_object_keys() {
+    let src = r#"{"mid":2.0,"name":"x","other":1}"#;
+
+    let mut threshold = None;
+    let mut threshold_key = None;
+    let mut name = None;
+    let mut name_key = None;
+
+    let mut desc = [
+        (
+            &["lo", "mid", "hi"][..],
+            &mut threshold_key,
+            qjson::Schema::Float(&mut threshold),
+        ),
+        (
+            &["id", "name"][..],
+            &mut name_key,
+            qjson::Schema::Str(&mut name),
+        ),
+    ];
+
+    qjson::from_str::<_, 1>(src, qjson::Schema::ObjectKeys(&mut desc)).unwrap();
+    assert_eq!(threshold_key, Some(1));
+    assert!((threshold.unwrap() - 2.0).abs() < 1e-9);
+    assert_eq!(name_key, Some(1));
+    assert_eq!(name, Some("x"));
+}
+
+#[test]
+fn ok_object_keys_empty_object() {
+    let mut threshold = Some(1.0);
+    let mut threshold_key = Some(0);
+    let mut desc = [(
+        &["lo", "hi"][..],
+        &mut threshold_key,
+        qjson::Schema::Float(&mut threshold),
+    )];
+
+    qjson::from_str::<_, 1>("{}", qjson::Schema::ObjectKeys(&mut desc)).unwrap();
+    assert!(threshold.is_none());
+    assert!(threshold_key.is_none());
+}
+
+#[test]
+fn ok_from_str_count() {
+    let src = r#"{"a":1,"b":{"c":"x","d":null},"e":[true],"f":2}"#;
+
+    let mut a = None;
+    let mut c = None;
+    let mut d = None;
+    let mut e = None;
+    let mut g = None;
+
+    let mut desc = qjson::schema!({
+        "a": Integer(&mut a),
+        "b": { "c": Str(&mut c), "d": Float(&mut d) },
+        "e": [Bool(&mut e)],
+        "g": Integer(&mut g),
+    });
+
+    let count = qjson::from_str_count::<_, 2>(src, &mut desc).unwrap();
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn ok_validate_out_of_range_number() {
+    qjson::validate::<1>("[123456789012345678901234567890]").unwrap();
+}
+
+#[test]
+fn err_out_of_range_integer() {
+    let mut i = None;
+    let mut desc = [qjson::Schema::Integer(&mut i)];
+    let src = "[123456789012345678901234567890]";
+    let err = qjson::from_str::<_, 1>(src, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::NumberOutOfRange);
+    assert_eq!((err.lineno(), err.col()), (1, 2));
+}
+
+#[test]
+fn err_out_of_range_integer_position() {
+    let src = "{\n  \"n\": 99999999999999999999,\n  \"a\": [1, 99999999999999999999]\n}";
+
+    let mut n = None;
+    let mut desc = [("n", qjson::Schema::Integer(&mut n))];
+    let err = qjson::from_str::<_, 2>(src, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::NumberOutOfRange);
+    assert_eq!((err.lineno(), err.col()), (2, 8));
+
+    let mut a = [0; 2];
+    let mut desc = [("a", qjson::Schema::IntArray(&mut a))];
+    let err = qjson::from_str::<_, 2>(src, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::NumberOutOfRange);
+    assert_eq!((err.lineno(), err.col()), (3, 12));
+
+    let mut n = None;
+    let mut desc = [("n", qjson::Schema::Integer(&mut n))];
+    let mut stack = [qjson::Frame::new(); 1];
+    let err = qjson::from_str_stack(src, &mut desc, &mut stack).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::NumberOutOfRange);
+    assert_eq!((err.lineno(), err.col()), (2, 8));
+}
+
+#[test]
+fn err_malformed_number() {
+    for src in [
+        "[01]", "[1.]", "[-]", "[1-2]", "[1.2.3]", "[1e]", "[1e+]", "[1E-]", "[1.e5]", "[1e5e5]",
+    ] {
+        let err = qjson::validate::<1>(src).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::InvalidNumber, "{src}");
+    }
+}
+
+#[test]
+fn ok_exponent() {
+    let (mut a, mut b, mut c, mut d) = (None, None, None, None);
+    let mut desc = [
+        ("a", qjson::Schema::Float(&mut a)),
+        ("b", qjson::Schema::Float(&mut b)),
+        ("c", qjson::Schema::Float(&mut c)),
+        ("d", qjson::Schema::Float(&mut d)),
+    ];
+    let src = r#"{"a": 1.5E-9, "b": 1e10, "c": 6.022e23, "d": -2e+2}"#;
+    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+    assert!((a.unwrap() - 1.5e-9).abs() < 1e-18);
+    assert!((b.unwrap() - 1e10).abs() < 1e-6);
+    assert!((c.unwrap() / 6.022e23 - 1.0).abs() < 1e-12);
+    assert!((d.unwrap() + 200.0).abs() < 1e-9);
+}
+
+#[test]
+fn err_exponent_into_integer() {
+    let mut i = None;
+    let mut desc = [qjson::Schema::Integer(&mut i)];
+    let err = qjson::from_str::<_, 1>("[1e10]", &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+}
+
+#[test]
+fn err_float_into_integer() {
+    let mut i = None;
+    let mut desc = [qjson::Schema::Integer(&mut i)];
+    let err = qjson::from_str::<_, 1>("[1.5]", &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+}
+
+#[test]
+fn ok_width_specific_integers() {
+    let src = r#"{"a":-128,"b":-32768,"c":-2147483648,"d":255,"e":65535,"f":4294967295,"g":18446744073709551615}"#;
+
+    let mut a = None;
+    let mut b = None;
+    let mut c = None;
+    let mut d = None;
+    let mut e = None;
+    let mut f = None;
+    let mut g = None;
+
+    let mut desc = qjson::schema!({
+        "a": I8(&mut a),
+        "b": I16(&mut b),
+        "c": I32(&mut c),
+        "d": U8(&mut d),
+        "e": U16(&mut e),
+        "f": U32(&mut f),
+        "g": U64(&mut g),
+    });
+
+    qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+
+    assert_eq!(a, Some(i8::MIN));
+    assert_eq!(b, Some(i16::MIN));
+    assert_eq!(c, Some(i32::MIN));
+    assert_eq!(d, Some(u8::MAX));
+    assert_eq!(e, Some(u16::MAX));
+    assert_eq!(f, Some(u32::MAX));
+    assert_eq!(g, Some(u64::MAX));
+}
+
+#[test]
+fn err_width_specific_integer_out_of_range() {
+    for src in ["[256]", "[-1]"] {
+        let mut u = None;
+        let mut desc = [qjson::Schema::U8(&mut u)];
+        let err = qjson::from_str::<_, 1>(src, &mut desc).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::NumberOutOfRange);
+        assert!(u.is_none());
+    }
+
+    let mut i = None;
+    let mut desc = [qjson::Schema::I16(&mut i)];
+    let err = qjson::from_str::<_, 1>("[32768]", &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::NumberOutOfRange);
+    assert!(i.is_none());
+}
+
+#[test]
+fn ok_durations() {
+    let mut timeout = None;
+    let mut jitter = None;
+    let mut desc = [
+        ("timeout_ms", qjson::Schema::Millis(&mut timeout)),
+        ("jitter_us", qjson::Schema::Micros(&mut jitter)),
+    ];
+    qjson::from_str::<_, 1>(r#"{"timeout_ms": 1500, "jitter_us": 0}"#, &mut desc).unwrap();
+    assert_eq!(timeout, Some(1500));
+    assert_eq!(jitter, Some(0));
+}
+
+#[test]
+fn err_durations() {
+    for (src, kind) in [
+        ("[-1]", qjson::ErrorKind::NumberOutOfRange),
+        ("[1.5]", qjson::ErrorKind::MismatchedTypes),
+        (r#"["1"]"#, qjson::ErrorKind::MismatchedTypes),
+    ] {
+        let mut ms = None;
+        let mut desc = [qjson::Schema::Millis(&mut ms)];
+        let err = qjson::from_str::<_, 1>(src, &mut desc).unwrap_err();
+        assert_eq!(err.kind(), kind);
+        assert!(ms.is_none());
+    }
+}
+
+#[test]
+fn ok_enum() {
+    let mut mode = None;
+    let mut desc = qjson::schema!({
+        "mode": Enum { out: &mut mode, variants: &["slow", "fast"] },
+    });
+    qjson::from_str::<_, 1>(r#"{"mode":"fast"}"#, &mut desc).unwrap();
+    assert_eq!(mode, Some(1));
+}
+
+#[test]
+fn err_enum_unknown_variant() {
+    let mut mode = None;
+    let mut desc = [qjson::Schema::Enum {
+        out: &mut mode,
+        variants: &["slow", "fast"],
+    }];
+    let err = qjson::from_str::<_, 1>(r#"["medium"]"#, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnknownVariant);
+    assert!(mode.is_none());
+}
+
+const PERMS: &[(&str, u64)] = &[("read", 0b001), ("write", 0b010), ("exec", 0b100)];
+
+#[test]
+fn ok_flags() {
+    let mut perms = None;
+    let mut desc = qjson::schema!({
+        "perms": Flags { out: &mut perms, names: PERMS },
+    });
+    qjson::from_str::<_, 2>(r#"{"perms":["read","exec"]}"#, &mut desc).unwrap();
+    assert_eq!(perms, Some(0b101));
+}
+
+#[test]
+fn ok_flags_empty() {
+    let mut perms = None;
+    let mut desc = [qjson::Schema::Flags {
+        out: &mut perms,
+        names: PERMS,
+    }];
+    qjson::from_str::<_, 2>("[[]]", &mut desc).unwrap();
+    assert_eq!(perms, Some(0));
+}
+
+#[test]
+fn err_flags_unknown_variant() {
+    let mut perms = None;
+    let mut desc = [qjson::Schema::Flags {
+        out: &mut perms,
+        names: PERMS,
+    }];
+    let err = qjson::from_str::<_, 2>(r#"[["read","delete"]]"#, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnknownVariant);
+    assert!(perms.is_none());
+}
+
+#[test]
+fn err_flags_mismatched_types() {
+    let mut perms = None;
+    let mut desc = [qjson::Schema::Flags {
+        out: &mut perms,
+        names: PERMS,
+    }];
+    let err = qjson::from_str::<_, 2>(r#"[["read",1]]"#, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+}
+
+#[test]
+fn ok_scalar_root_number_at_eof() {
+    for src in ["42", "42 ", "42\n", " 42\r\n"] {
+        let mut i = None;
+        qjson::from_str::<_, 0>(src, &mut i).unwrap();
+        assert_eq!(i, Some(42), "{src:?}");
+    }
+    for src in ["-1.5", "-1.5\n"] {
+        let mut f = None;
+        qjson::from_str::<_, 0>(src, &mut f).unwrap();
+        assert_eq!(f, Some(-1.5), "{src:?}");
+    }
+}
+
+#[test]
+fn ok_validate_scalar_root_number_at_eof() {
+    for src in ["0", "0\n", "-0", "1.25", "1.25\n", "123", "-0.5", "1e5"] {
+        qjson::validate::<0>(src).unwrap();
+    }
+
+    let (tok, span) = qjson::tokens("123").next().unwrap().unwrap();
+    assert_eq!(tok, qjson::Token::Number("123"));
+    assert_eq!((span.start, span.end), (0, 3));
+}
+
+#[test]
+fn ok_from_bytes() {
+    let mut a = None;
+    let mut desc = [("a", qjson::Schema::Str(&mut a))];
+    qjson::from_bytes::<_, 1>("{\"a\": \"é\"}".as_bytes(), &mut desc).unwrap();
+    assert_eq!(a, Some("é"));
+
+    qjson::validate_bytes::<2>(b"[1, {}]").unwrap();
+}
+
+#[test]
+fn err_from_bytes_invalid_utf8() {
+    for (src, offset, pos) in [
+        (&b"\xff"[..], 0, (1, 1)),
+        (b"[\"\xc3\"]", 2, (1, 3)),
+        (b"{\"\xc3\xa9\":\n  \"\xff\"}", 10, (2, 4)),
+        (b"[1, 2\xe2\x82]", 5, (1, 6)),
+    ] {
+        let err = qjson::validate_bytes::<1>(src).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::InvalidUtf8, "{src:?}");
+        assert_eq!(err.offset(), offset, "{src:?}");
+        assert_eq!((err.lineno(), err.col()), pos, "{src:?}");
+    }
+
+    let mut a = None;
+    let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+    let err = qjson::from_bytes::<_, 1>(b"{\"a\":1}\x80", &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::InvalidUtf8);
+    assert_eq!(err.offset(), 7);
+    assert_eq!(a, None);
+}
+
+#[test]
+fn err_offset() {
+    for (src, kind, pos, offset) in [
+        ("[1,]", qjson::ErrorKind::UnexpectedTrailingComma, (1, 4), 3),
+        (
+            "[\"é\", tru]",
+            qjson::ErrorKind::UnknownIdentifier,
+            (1, 10),
+            10,
+        ),
+        (
+            "{\n  \"😀\": x}",
+            qjson::ErrorKind::UnknownStartOfToken,
+            (2, 8),
+            12,
+        ),
+        ("[1, 2", qjson::ErrorKind::UnexpectedEof, (1, 5), 5),
+        ("{\"a\":[}", qjson::ErrorKind::MismatchedBracket, (1, 7), 6),
+    ] {
+        let err = qjson::validate::<2>(src).unwrap_err();
+        assert_eq!(err.kind(), kind, "{src:?}");
+        assert_eq!((err.lineno(), err.col()), pos, "{src:?}");
+        assert_eq!(err.offset(), offset, "{src:?}");
+        assert!(src.is_char_boundary(err.offset()), "{src:?}");
+    }
+}
+
+#[test]
+fn ok_from_str_mut_retry() {
+    let mut a = None;
+    let mut b = None;
+    {
+        let mut desc = qjson::schema!({ "a": Integer(&mut a), "b": Str(&mut b) });
+        let mut schema = qjson::Schema::from(&mut desc);
+
+        let err = qjson::from_str_mut::<1>(r#"{"a":1,"b":2}"#, &mut schema).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+        qjson::from_str_mut::<1>(r#"{"a":3,"b":"x"}"#, &mut schema).unwrap();
+    }
+    assert_eq!(a, Some(3));
+    assert_eq!(b, Some("x"));
+}
+
+#[test]
+fn err_from_str_mut_clears_schema() {
+    let mut a = Some(0);
+    let mut b = Some("stale");
+    {
+        let mut desc = qjson::schema!({ "a": Integer(&mut a), "b": Str(&mut b) });
+        let mut schema = qjson::Schema::from(&mut desc);
+        qjson::from_str_mut::<1>(r#"{"a":1,"b":"x",}"#, &mut schema).unwrap_err();
+    }
+    assert!(a.is_none());
+    assert!(b.is_none());
+}
+
+#[test]
+fn ok_root_keys() {
+    let mut keys = [""; 3];
+    let src = r#"{"a":1,"b":[2,3],"c":{"d":null}}"#;
+    let n = qjson::root_keys::<2>(src, &mut keys).unwrap();
+    assert_eq!(&keys[..n], ["a", "b", "c"]);
+
+    let n = qjson::root_keys::<1>("{}", &mut keys).unwrap();
+    assert_eq!(n, 0);
+}
+
+#[test]
+fn err_root_keys() {
+    let mut keys = [""; 1];
+    let err = qjson::root_keys::<1>(r#"{"a":1,"b":2}"#, &mut keys).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::InsufficientArrayLength);
+    let err = qjson::root_keys::<1>("[1]", &mut keys).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+    let err = qjson::root_keys::<1>(r#"{"a":1,}"#, &mut keys).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedTrailingComma);
+}
+
+#[test]
+fn ok_duplicate_keys_last_wins() {
+    let mut a = None;
+    let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+    qjson::from_str::<_, 1>(r#"{"a":1,"a":2}"#, &mut desc).unwrap();
+    assert_eq!(a, Some(2));
+}
+
+#[test]
+fn ok_duplicate_keys_first_wins() {
+    let mut a = None;
+    let mut b = None;
+    let mut c = None;
+    let mut desc = qjson::schema!({
+        "a": Integer(&mut a),
+        "b": Str(&mut b),
+        "o": { "c": Bool(&mut c) },
+    });
+    let src = r#"{"a":1,"b":"x","o":{"c":true},"a":2,"b":null,"o":{"c":false}}"#;
+    let mut opts = qjson::Options::new();
+    opts.first_key_wins = true;
+    qjson::from_str_with::<_, 2>(src, &mut desc, opts).unwrap();
+    assert_eq!(a, Some(1));
+    assert_eq!(b, Some("x"));
+    assert_eq!(c, Some(true));
+}
+
+#[test]
+fn ok_duplicate_keys_first_wins_nested_and_null() {
+    let opts = qjson::Options {
+        first_key_wins: true,
+        ..Default::default()
+    };
+    for stack in [false, true] {
+        for (src, expected) in [
+            (r#"{"o":{"c":true},"o":null}"#, (Some(true), Some(0))),
+            (r#"{"o":{"c":true},"o":{}}"#, (Some(true), Some(0))),
+            (r#"{"a":null,"a":2}"#, (None, None)),
+            (
+                r#"{"a":1,"o":{"c":false,"c":true},"a":2}"#,
+                (Some(false), Some(1)),
+            ),
+        ] {
+            let (mut a, mut c) = (Some(0), None);
+            let mut desc = qjson::schema!({
+                "a": Integer(&mut a),
+                "o": { "c": Bool(&mut c) },
+            });
+            if stack {
+                let mut stack = [qjson::Frame::new(); 2];
+                qjson::from_str_stack_with(src, &mut desc, &mut stack, opts).unwrap();
+            } else {
+                qjson::from_str_with::<_, 2>(src, &mut desc, opts).unwrap();
+            }
+            assert_eq!((c, a), expected, "{src}");
+        }
+    }
+}
+
+#[test]
+fn ok_duplicate_keys_first_wins_object_kinds() {
+    let opts = qjson::Options {
+        first_key_wins: true,
+        ..Default::default()
+    };
+    for stack in [false, true] {
+        let mut slots = [None, None];
+        let [s0, s1] = &mut slots;
+        let mut desc = [qjson::Schema::Integer(s0), qjson::Schema::Integer(s1)];
+        let src = r#"{"1":null,"0":1,"1":2,"0":3}"#;
+        let desc = qjson::Schema::IndexedObject(&mut desc);
+        if stack {
+            let mut stack = [qjson::Frame::new(); 1];
+            qjson::from_str_stack_with(src, desc, &mut stack, opts).unwrap();
+        } else {
+            qjson::from_str_with::<_, 1>(src, desc, opts).unwrap();
+        }
+        assert_eq!(slots, [Some(1), None]);
+
+        let (mut lo, mut key) = (None, None);
+        let mut desc = [(&["lo", "min"][..], &mut key, qjson::Schema::Float(&mut lo))];
+        let src = r#"{"min":null,"lo":1.5}"#;
+        let desc = qjson::Schema::ObjectKeys(&mut desc);
+        if stack {
+            let mut stack = [qjson::Frame::new(); 1];
+            qjson::from_str_stack_with(src, desc, &mut stack, opts).unwrap();
+        } else {
+            qjson::from_str_with::<_, 1>(src, desc, opts).unwrap();
+        }
+        assert_eq!((lo, key), (None, Some(1)));
+
+        let (mut text, mut arm) = (None, None);
+        let mut label = [("text", qjson::Schema::Str(&mut text))];
+        let mut arms = [("label", &mut label[..])];
+        let src = r#"{"type":"label","text":null,"text":"x"}"#;
+        let desc = qjson::Schema::Tagged {
+            tag: "type",
+            out: &mut arm,
+            arms: &mut arms,
+        };
+        if stack {
+            let mut stack = [qjson::Frame::new(); 1];
+            qjson::from_str_stack_with(src, desc, &mut stack, opts).unwrap();
+        } else {
+            qjson::from_str_with::<_, 1>(src, desc, opts).unwrap();
+        }
+        assert_eq!((arm, text), (Some(0), None));
+    }
+}
+
+#[test]
+fn ok_duplicate_keys_first_wins_past_64_fields() {
+    let opts = qjson::Options {
+        first_key_wins: true,
+        ..Default::default()
+    };
+    let names: Vec<String> = (0..70).map(|i| format!("f{i}")).collect();
+    let src = r#"{"f65":1,"f3":2,"x":{"f69":0},"f69":null,"f65":3,"f69":4,"f3":5,"f65":6}"#;
+    for how in 0..3 {
+        let mut slots = [None; 70];
+        let mut desc: Vec<_> = names
+            .iter()
+            .zip(&mut slots)
+            .map(|(name, slot)| (name.as_str(), qjson::Schema::Integer(slot)))
+            .collect();
+        let mut scratch = [0; 128];
+        match how {
+            0 => qjson::from_str_with::<_, 2>(src, &mut desc[..], opts).unwrap(),
+            1 => {
+                let mut stack = [qjson::Frame::new(); 2];
+                qjson::from_str_stack_with(src, &mut desc[..], &mut stack, opts).unwrap()
+            }
+            _ => {
+                let chars = src.chars();
+                qjson::from_chars_with::<_, _, 2>(chars, &mut scratch, &mut desc[..], opts).unwrap()
+            }
+        }
+        assert_eq!((slots[3], slots[65], slots[69]), (Some(2), Some(1), None));
+    }
+
+    let mut slots = [None; 70];
+    let mut desc: Vec<_> = slots.iter_mut().map(qjson::Schema::Integer).collect();
+    let src = r#"{"66":1,"1":2,"67":3,"66":4,"67":5}"#;
+    let desc = qjson::Schema::IndexedObject(&mut desc);
+    qjson::from_str_with::<_, 1>(src, desc, opts).unwrap();
+    assert_eq!(
+        (slots[1], slots[66], slots[67]),
+        (Some(2), Some(1), Some(3))
+    );
+}
+
+#[test]
+fn ok_object_capture() {
+    let mut pairs = [("", ""); 4];
+    let mut count = 0;
+    let mut desc = [(
+        "extra",
+        qjson::Schema::ObjectCapture(&mut pairs, &mut count),
+    )];
+    let src = r#"{"extra": {"a": 1.5, "b":"x" , "c": [1, {"d": null}], "e": true}}"#;
+    qjson::from_str::<_, 4>(src, &mut desc).unwrap();
+    assert_eq!(count, 4);
+    assert_eq!(
+        pairs,
+        [
+            ("a", "1.5"),
+            ("b", r#""x""#),
+            ("c", r#"[1, {"d": null}]"#),
+            ("e", "true"),
+        ]
+    );
+}
+
+#[test]
+fn ok_object_capture_source_order() {
+    for first_key_wins in [false, true] {
+        let mut pairs = [("", ""); 4];
+        let mut count = 0;
+        let mut desc = [qjson::Schema::ObjectCapture(&mut pairs, &mut count)];
+        let opts = qjson::Options {
+            first_key_wins,
+            ..Default::default()
+        };
+        let src = r#"[{"z": 1, "a": 2, "z": 3, "m": 4}]"#;
+        qjson::from_str_with::<_, 2>(src, &mut desc, opts).unwrap();
+        assert_eq!(count, 4);
+        assert_eq!(pairs, [("z", "1"), ("a", "2"), ("z", "3"), ("m", "4")]);
+    }
+}
+
+#[test]
+fn ok_object_capture_empty() {
+    let mut pairs = [("", ""); 1];
+    let mut count = 1;
+    let mut desc = [qjson::Schema::ObjectCapture(&mut pairs, &mut count)];
+    qjson::from_str::<_, 2>("[{}]", &mut desc).unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn ok_raw() {
+    let (mut inner, mut n) = (None, None);
+    let mut desc = [
+        ("inner", qjson::Schema::Raw(&mut inner)),
+        ("n", qjson::Schema::Integer(&mut n)),
+    ];
+    qjson::from_str::<_, 3>(r#"{"inner":{"a":1},"n":2}"#, &mut desc).unwrap();
+    assert_eq!(inner, Some(r#"{"a":1}"#));
+    assert_eq!(n, Some(2));
+}
+
+#[test]
+fn ok_raw_values() {
+    let src = r#"[ "x\"y" , -1.5e3, null, [ 1, [] ], {}, { "a" : [ {} ] } ]"#;
+    let expected = [
+        r#""x\"y""#,
+        "-1.5e3",
+        "null",
+        "[ 1, [] ]",
+        "{}",
+        r#"{ "a" : [ {} ] }"#,
+    ];
+
+    let mut out = [None; 6];
+    {
+        let [a, b, c, d, e, f] = &mut out;
+        let mut desc = qjson::tuple_schema![
+            qjson::Schema::Raw(a),
+            qjson::Schema::Raw(b),
+            qjson::Schema::Raw(c),
+            qjson::Schema::Raw(d),
+            qjson::Schema::Raw(e),
+            qjson::Schema::Raw(f),
+        ];
+        qjson::from_str::<_, 4>(src, &mut desc).unwrap();
+    }
+    assert_eq!(out, expected.map(Some));
+
+    let mut out = [None; 6];
+    {
+        let [a, b, c, d, e, f] = &mut out;
+        let mut desc = qjson::tuple_schema![
+            qjson::Schema::Raw(a),
+            qjson::Schema::Raw(b),
+            qjson::Schema::Raw(c),
+            qjson::Schema::Raw(d),
+            qjson::Schema::Raw(e),
+            qjson::Schema::Raw(f),
+        ];
+        let mut stack = [qjson::Frame::new(); 4];
+        qjson::from_str_stack(src, &mut desc, &mut stack).unwrap();
+    }
+    assert_eq!(out, expected.map(Some));
+}
+
+#[test]
+fn ok_tagged() {
+    for src in [
+        r#"{"type": "temperature", "value": 21.5}"#,
+        r#"{"value": 21.5, "extra": [{"type": "label"}], "type": "temperature"}"#,
+        r#"{"type": "label", "text": "x"}"#,
+    ] {
+        for stack in [false, true] {
+            let (mut value, mut text, mut arm) = (None, None, None);
+            let mut temperature = [("value", qjson::Schema::Float(&mut value))];
+            let mut label = [("text", qjson::Schema::Str(&mut text))];
+            let mut arms = [
+                ("temperature", &mut temperature[..]),
+                ("label", &mut label[..]),
+            ];
+            let desc = qjson::Schema::Tagged {
+                tag: "type",
+                out: &mut arm,
+                arms: &mut arms,
+            };
+            if stack {
+                let mut stack = [qjson::Frame::new(); 3];
+                qjson::from_str_stack(src, desc, &mut stack).unwrap();
+            } else {
+                qjson::from_str::<_, 3>(src, desc).unwrap();
+            }
+            if src.contains("temperature") {
+                assert_eq!((arm, value, text), (Some(0), Some(21.5), None), "{src}");
+            } else {
+                assert_eq!((arm, value, text), (Some(1), None, Some("x")), "{src}");
+            }
+        }
+    }
+
+    let src = r#"[{"type":"temperature","value":21.5},{"type":"label","text":"x"}]"#;
+    for stack in [false, true] {
+        let (mut value0, mut text0, mut arm0) = (None, None, None);
+        let (mut value1, mut text1, mut arm1) = (None, None, None);
+        let mut temperature0 = [("value", qjson::Schema::Float(&mut value0))];
+        let mut label0 = [("text", qjson::Schema::Str(&mut text0))];
+        let mut arms0 = [
+            ("temperature", &mut temperature0[..]),
+            ("label", &mut label0[..]),
+        ];
+        let mut temperature1 = [("value", qjson::Schema::Float(&mut value1))];
+        let mut label1 = [("text", qjson::Schema::Str(&mut text1))];
+        let mut arms1 = [
+            ("temperature", &mut temperature1[..]),
+            ("label", &mut label1[..]),
+        ];
+        let mut desc = [
+            qjson::Schema::Tagged {
+                tag: "type",
+                out: &mut arm0,
+                arms: &mut arms0,
+            },
+            qjson::Schema::Tagged {
+                tag: "type",
+                out: &mut arm1,
+                arms: &mut arms1,
+            },
+        ];
+        let desc = qjson::Schema::Array(&mut desc);
+        if stack {
+            let mut stack = [qjson::Frame::new(); 2];
+            qjson::from_str_stack(src, desc, &mut stack).unwrap();
+        } else {
+            qjson::from_str::<_, 2>(src, desc).unwrap();
+        }
+        assert_eq!((arm0, value0, text0), (Some(0), Some(21.5), None));
+        assert_eq!((arm1, value1, text1), (Some(1), None, Some("x")));
+    }
+}
+
+#[derive(Default)]
+struct Reading<'a> {
+    name: Option<&'a str>,
+    values: [Option<f64>; 2],
+}
+
+impl<'a> qjson::FromJson<'a> for Reading<'a> {
+    fn with_schema<R>(&mut self, f: impl FnOnce(qjson::Schema<'a, '_>) -> R) -> R {
+        let [v0, v1] = &mut self.values;
+        let mut values = [qjson::Schema::Float(v0), qjson::Schema::Float(v1)];
+        let mut desc = [
+            ("name", qjson::Schema::Str(&mut self.name)),
+            ("values", qjson::Schema::Array(&mut values)),
+        ];
+        f(qjson::Schema::Object(&mut desc))
+    }
+}
+
+#[test]
+fn ok_from_json() {
+    use qjson::FromJson;
+
+    let src = r#"{"name":"a","values":[1.5,2.0]}"#;
+    let mut reading = Reading::default();
+    reading
+        .with_schema(|desc| qjson::from_str::<_, 2>(src, desc))
+        .unwrap();
+    assert_eq!(reading.name, Some("a"));
+    assert_eq!(reading.values, [Some(1.5), Some(2.0)]);
+
+    let mut out = String::new();
+    reading
+        .with_schema(|desc| qjson::to_writer(&mut out, desc))
+        .unwrap();
+    assert_eq!(out, src);
+}
+
+#[test]
+fn err_object_capture_insufficient_array_length() {
+    let mut pairs = [("", ""); 1];
+    let mut count = 0;
+    let mut desc = [qjson::Schema::ObjectCapture(&mut pairs, &mut count)];
+    let err = qjson::from_str::<_, 2>(r#"[{"a":1,"b":2}]"#, &mut desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::InsufficientArrayLength);
+}
+
+#[test]
+fn ok_from_str_pos() {
+    let mut s = None;
+    let pos = qjson::from_str_pos::<_, 0>(r#""abc""#, &mut s).unwrap();
+    assert_eq!(pos, (1, 1));
+    assert_eq!(s, Some("abc"));
+
+    let mut i = None;
+    let pos = qjson::from_str_pos::<_, 0>("\r\n\n\t 1234 ", &mut i).unwrap();
+    assert_eq!(pos, (3, 3));
+    assert_eq!(i, Some(1234));
+}
+
+#[test]
+fn ok_null_keeps_default() {
+    let mut a = Some(1);
+    let mut b = Some("default");
+    let mut c = Some(true);
+    let mut desc = qjson::schema!({
+        "a": Integer(&mut a),
+        "b": Str(&mut b),
+        "o": { "c": Bool(&mut c) },
+    });
+    let mut opts = qjson::Options::new();
+    opts.null_keeps_default = true;
+    qjson::from_str_with::<_, 2>(r#"{"a":null,"b":null,"o":null}"#, &mut desc, opts).unwrap();
+    assert_eq!(a, Some(1));
+    assert_eq!(b, Some("default"));
+    assert_eq!(c, None);
+}
+
+#[test]
+fn ok_null_clears_by_default() {
+    let mut a = Some(1);
+    let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+    qjson::from_str::<_, 1>(r#"{"a":null}"#, &mut desc).unwrap();
+    assert_eq!(a, None);
+}
+
+/// Binds `{"network": {"proxy": {"port": _}}}` with presence flags,
+/// returning whether `network` and `proxy` were present.
+fn optional_objects(src: &str, stack: bool) -> (bool, bool, Option<i64>) {
+    let (mut network, mut proxy, mut port) = (false, false, None);
+    let mut proxy_desc = [("port", qjson::Schema::Integer(&mut port))];
+    let mut network_desc = [(
+        "proxy",
+        qjson::Schema::OptionalObject {
+            present: &mut proxy,
+            fields: &mut proxy_desc,
+        },
+    )];
+    let mut desc = [(
+        "network",
+        qjson::Schema::OptionalObject {
+            present: &mut network,
+            fields: &mut network_desc,
+        },
+    )];
+    if stack {
+        let mut frames = [qjson::Frame::new(); 3];
+        qjson::from_str_stack(src, &mut desc, &mut frames).unwrap();
+    } else {
+        qjson::from_str::<_, 3>(src, &mut desc).unwrap();
+    }
+    (network, proxy, port)
+}
+
+#[test]
+fn ok_optional_object() {
+    for stack in [false, true] {
+        assert_eq!(optional_objects("{}", stack), (false, false, None));
+        assert_eq!(
+            optional_objects(r#"{"network":{}}"#, stack),
+            (true, false, None)
+        );
+        assert_eq!(
+            optional_objects(r#"{"network":{"proxy":{}}}"#, stack),
+            (true, true, None)
+        );
+        assert_eq!(
+            optional_objects(r#"{"network":{"proxy":{"port":8080}}}"#, stack),
+            (true, true, Some(8080))
+        );
+        assert_eq!(
+            optional_objects(r#"{"network":{"proxy":null}}"#, stack),
+            (true, false, None)
+        );
+        assert_eq!(
+            optional_objects(r#"{"network":null}"#, stack),
+            (false, false, None)
+        );
+    }
+}
+
+/// Binds `src` with a schema for each kind of root value.
+fn bind_any(src: &str) -> (qjson::ValueKind, Option<i64>, [Option<i64>; 2], Option<i64>) {
+    let (mut field, mut items, mut scalar) = (None, [None, None], None);
+    let kind = {
+        let [i0, i1] = &mut items;
+        let mut object = [("i", qjson::Schema::Integer(&mut field))];
+        let mut array = [qjson::Schema::Integer(i0), qjson::Schema::Integer(i1)];
+        let mut roots = qjson::RootSchemas {
+            object: Some(qjson::Schema::from(&mut object)),
+            array: Some(qjson::Schema::from(&mut array)),
+            scalar: Some(qjson::Schema::Integer(&mut scalar)),
+        };
+        qjson::from_str_any::<1>(src, &mut roots).unwrap()
+    };
+    (kind, field, items, scalar)
+}
+
+#[test]
+fn ok_from_str_any() {
+    use qjson::ValueKind::*;
+
+    assert_eq!(
+        bind_any(r#"{"i":1}"#),
+        (Object, Some(1), [None, None], None)
+    );
+    assert_eq!(bind_any("[2, 3]"), (Array, None, [Some(2), Some(3)], None));
+    assert_eq!(bind_any(" 4"), (Number, None, [None, None], Some(4)));
+    assert_eq!(bind_any("null"), (Null, None, [None, None], None));
+
+    let mut roots = qjson::RootSchemas::default();
+    let kind = qjson::from_str_any::<2>(r#"{"a":[]}"#, &mut roots).unwrap();
+    assert_eq!(kind, Object);
+}
+
+#[test]
+fn err_from_str_any() {
+    let mut s = None;
+    let mut roots = qjson::RootSchemas {
+        scalar: Some(qjson::Schema::Str(&mut s)),
+        ..Default::default()
+    };
+    let err = qjson::from_str_any::<1>("1", &mut roots).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+    let err = qjson::from_str_any::<1>("[1,]", &mut roots).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedTrailingComma);
+    let err = qjson::from_str_any::<1>("", &mut roots).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn ok_with_options() {
+    let opts = qjson::Options {
+        lenient: true,
+        ..Default::default()
+    };
+    let src = "{'a': 1, 'b': [2, 3.5]}";
+
+    let (mut a, mut b, mut c) = (None, None, None);
+    {
+        let mut arr = [qjson::Schema::Integer(&mut b), qjson::Schema::Float(&mut c)];
+        let mut desc = [
+            ("a", qjson::Schema::Integer(&mut a)),
+            ("b", qjson::Schema::Array(&mut arr)),
+        ];
+        let count = qjson::from_str_count_with::<_, 2>(src, &mut desc, opts).unwrap();
+        assert_eq!(count, 3);
+    }
+    assert_eq!((a, b, c), (Some(1), Some(2), Some(3.5)));
+
+    let mut a = None;
+    let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+    let pos = qjson::from_str_pos_with::<_, 2>(src, &mut desc, opts).unwrap();
+    assert_eq!(pos, (1, 1));
+
+    let mut a = None;
+    let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+    let (count, err) = qjson::from_str_best_effort_with::<_, 2>(src, &mut desc, opts);
+    assert_eq!((count, err.is_none()), (1, true));
+
+    let mut a = None;
+    let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+    let mut stack = [qjson::Frame::new(); 2];
+    qjson::from_str_stack_with(src, &mut desc, &mut stack, opts).unwrap();
+    qjson::validate_stack_with(src, &mut stack, opts).unwrap();
+
+    let mut a = None;
+    {
+        let mut desc = [("a", qjson::Schema::Integer(&mut a))];
+        let mut schema = qjson::Schema::from(&mut desc);
+        qjson::from_str_mut_with::<2>(src, &mut schema, opts).unwrap();
+    }
+    assert_eq!(a, Some(1));
+
+    let mut roots = qjson::RootSchemas::default();
+    let kind = qjson::from_str_any_with::<2>(src, &mut roots, opts).unwrap();
+    assert_eq!(kind, qjson::ValueKind::Object);
+    qjson::validate_roundtrip_with::<2>(src, opts).unwrap();
+
+    // the defaults are strict
+    assert!(qjson::validate_roundtrip::<2>(src).is_err());
+    assert!(qjson::validate_stack(src, &mut stack).is_err());
+}
+
+#[test]
+fn ok_from_chars() {
+    let src = "{\"s\": \"h\u{e9}llo\",\n \"n\": [1, 2]}";
+    let mut scratch = [0; 32];
+    let mut st = None;
+    let mut n = [0; 2];
+    qjson::from_chars::<_, _, 2>(
+        src.chars(),
+        &mut scratch,
+        &mut [
+            ("s", qjson::Schema::Str(&mut st)),
+            ("n", qjson::Schema::IntArray(&mut n)),
+        ],
+    )
+    .unwrap();
+    assert_eq!(st, Some("h\u{e9}llo"));
+    assert_eq!(n, [1, 2]);
+}
+
+#[test]
+fn ok_from_chars_scratch_holds_tokens_only() {
+    // the document is longer than the scratch buffer, its tokens are not
+    let src = "[\n    \"\u{e9}\u{e9}\u{e9}\",\n    1\n]";
+    let mut scratch = [0; 12];
+    let mut s = None;
+    let mut i = None;
+    let mut desc = [qjson::Schema::Str(&mut s), qjson::Schema::Integer(&mut i)];
+    qjson::from_chars::<_, _, 1>(src.chars(), &mut scratch, &mut desc[..]).unwrap();
+    assert_eq!((s, i), (Some("\u{e9}\u{e9}\u{e9}"), Some(1)));
+
+    let mut raw = None;
+    let mut scratch = [0; 16];
+    let src = "{ \"a\" : [ 1 , 2 ] }";
+    qjson::from_chars::<_, _, 2>(src.chars(), &mut scratch, qjson::Schema::Raw(&mut raw)).unwrap();
+    assert_eq!(raw, Some(r#"{"a":[1,2]}"#));
+}
+
+#[test]
+fn ok_from_chars_with() {
+    let mut opts = qjson::Options::new();
+    opts.lenient = true;
+    let mut scratch = [0; 16];
+    let mut s = None;
+    let mut desc = [("s", qjson::Schema::Str(&mut s))];
+    qjson::from_chars_with::<_, _, 1>("{'s':'x'}".chars(), &mut scratch, &mut desc, opts).unwrap();
+    assert_eq!(s, Some("x"));
+
+    let mut s = None;
+    let mut desc = [("s", qjson::Schema::Str(&mut s))];
+    let err = qjson::from_chars::<_, _, 1>("{'s':'x'}".chars(), &mut scratch, &mut desc);
+    assert_eq!(
+        err.unwrap_err().kind(),
+        qjson::ErrorKind::UnknownStartOfToken
+    );
+}
+
+#[test]
+fn err_from_chars_tagged() {
+    let mut scratch = [0; 32];
+    let (mut x, mut arm) = (None, None);
+    let mut a = [("x", qjson::Schema::Integer(&mut x))];
+    let mut arms = [("a", &mut a[..])];
+    let tagged = qjson::Schema::Tagged {
+        tag: "type",
+        out: &mut arm,
+        arms: &mut arms,
+    };
+    let src = r#"{"type":"a","x":1}"#;
+    let err = qjson::from_chars::<_, _, 1>(src.chars(), &mut scratch, tagged).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+}
+
+#[test]
+fn err_from_chars_insufficient_buffer() {
+    let mut raw = None;
+    let mut scratch = [0; 12];
+    let src = "[1,\n\"\u{e9}\u{e9}\u{e9}\u{e9}\"]";
+    let desc = qjson::Schema::Raw(&mut raw);
+    let err = qjson::from_chars::<_, _, 1>(src.chars(), &mut scratch, desc).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::InsufficientBuffer);
+    assert_eq!((err.lineno(), err.col(), err.offset()), (2, 6, 13));
+
+    // other errors are reported as usual
+    let mut i: Option<i64> = None;
+    let mut scratch = [0; 8];
+    let err = qjson::from_chars::<_, _, 1>("\n1 2".chars(), &mut scratch, &mut i).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedToken);
+    assert_eq!(err.lineno(), 2);
+}
+
+#[test]
+fn ok_max_container_len() {
+    let mut opts = qjson::Options::new();
+    opts.max_container_len = Some(2);
+    qjson::validate_with::<4>(r#"{"a":[1,2],"b":{"c":[],"d":{}}}"#, opts).unwrap();
+
+    let mut a = [0; 2];
+    let mut desc = [("a", qjson::Schema::IntArray(&mut a))];
+    qjson::from_str_with::<_, 2>(r#"{"a":[1,2]}"#, &mut desc, opts).unwrap();
+    assert_eq!(a, [1, 2]);
+}
+
+#[test]
+fn err_max_container_len() {
+    let mut opts = qjson::Options::new();
+    opts.max_container_len = Some(2);
+
+    for src in [
+        r#"[1,2,3]"#,
+        r#"{"a":1,"b":2,"c":3}"#,
+        r#"{"a":[[1,2],[1,2,3]]}"#,
+        r#"[{"a":{"b":1,"c":2,"d":3}}]"#,
+    ] {
+        let err = qjson::validate_with::<5>(src, opts).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::ContainerTooLarge, "{}", src);
+    }
+
+    let mut a = [0; 3];
+    let mut desc = [("a", qjson::Schema::IntArray(&mut a))];
+    let err = qjson::from_str_with::<_, 2>(r#"{"a":[1,2,3]}"#, &mut desc, opts).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::ContainerTooLarge);
+    assert_eq!(err.col(), 11);
+}
+
+#[test]
+fn ok_validate_roundtrip() {
+    let src = r#"{"a":[0, -0, 0.0, 0.1, -2.5, 1.50, 100, 0.000123, 9007199254740992]}"#;
+    qjson::validate_roundtrip::<2>(src).unwrap();
+    qjson::validate_roundtrip::<0>("1.7976931348623157").unwrap();
+}
+
+#[test]
+fn err_validate_roundtrip() {
+    for src in [
+        "9007199254740993",
+        "0.30000000000000001",
+        "-1.00000000000000000001",
+        "123456789012345678901234567890",
+    ] {
+        qjson::validate::<0>(src).unwrap();
+        let err = qjson::validate_roundtrip::<0>(src).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::PrecisionLoss, "{}", src);
+    }
+
+    let err = qjson::validate_roundtrip::<1>("[1,\n 2.00000000000000001]").unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::PrecisionLoss);
+    assert_eq!((err.lineno(), err.col()), (2, 20));
+}
+
+#[test]
+fn ok_validate_all_no_errors() {
+    let mut errs: [qjson::Error; 2] = Default::default();
+    assert_eq!(
+        qjson::validate_all::<3>(r#"{"a":[1,{"b":2}]}"#, &mut errs),
+        0
+    );
+}
+
+#[test]
+fn err_validate_all_object() {
+    let mut errs: [qjson::Error; 4] = Default::default();
+    let src = r#"{"b":{"c":[1 2]},"a":tru,"d":1,"e":01}"#;
+    let n = qjson::validate_all::<3>(src, &mut errs);
+    let kinds = errs[..n].iter().map(|err| err.kind()).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            qjson::ErrorKind::MissingComma,
+            qjson::ErrorKind::UnknownIdentifier,
+            qjson::ErrorKind::InvalidNumber,
+        ]
+    );
+}
+
+#[test]
+fn err_validate_all_array() {
+    let mut errs: [qjson::Error; 3] = Default::default();
+    let n = qjson::validate_all::<2>("[1,x,2,[3 4]", &mut errs);
+    let kinds = errs[..n].iter().map(|err| err.kind()).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            qjson::ErrorKind::UnknownStartOfToken,
+            qjson::ErrorKind::MissingComma,
+            qjson::ErrorKind::UnexpectedEof,
+        ]
+    );
+}
+
+#[test]
+fn err_validate_all_misspelt_literal() {
+    let mut errs: [qjson::Error; 4] = Default::default();
+    for (src, expected) in [
+        (
+            r#"{"a":{"b":tru},"c":1,"d":[1,,2],"e":01}"#,
+            &[
+                (qjson::ErrorKind::UnknownIdentifier, 13),
+                (qjson::ErrorKind::UnexpectedToken, 28),
+                (qjson::ErrorKind::InvalidNumber, 37),
+            ][..],
+        ),
+        (
+            "[[tru],nul[1],x]",
+            &[
+                (qjson::ErrorKind::UnknownIdentifier, 5),
+                (qjson::ErrorKind::UnknownIdentifier, 10),
+                (qjson::ErrorKind::UnknownStartOfToken, 14),
+            ][..],
+        ),
+    ] {
+        let n = qjson::validate_all::<3>(src, &mut errs);
+        let found = errs[..n]
+            .iter()
+            .map(|err| (err.kind(), err.offset()))
+            .collect::<Vec<_>>();
+        assert_eq!(found, expected, "{src}");
+    }
+}
+
+#[test]
+fn err_validate_all_capacity() {
+    let mut errs: [qjson::Error; 1] = Default::default();
+    let n = qjson::validate_all::<1>(r#"{"a":x,"b":y}"#, &mut errs);
+    assert_eq!(n, 1);
+    assert_eq!(errs[0].kind(), qjson::ErrorKind::UnknownStartOfToken);
+}
+
+#[test]
+fn err_validate_all_trailing() {
+    let mut errs: [qjson::Error; 3] = Default::default();
+    for (src, col) in [("{} 1 2", 4), ("[1,x] {} [", 7), ("1 } ]", 3)] {
+        let n = qjson::validate_all::<1>(src, &mut errs);
+        // the trailing tokens are reported once, at the first
+        let last = &errs[n - 1];
+        assert_eq!(last.kind(), qjson::ErrorKind::UnexpectedToken, "{src}");
+        assert_eq!(last.col(), col, "{src}");
+        assert_eq!(n, if src.contains('x') { 2 } else { 1 }, "{src}");
+    }
+}
+
+#[test]
+fn err_validate_all_with_options() {
+    let mut errs: [qjson::Error; 2] = Default::default();
+    let src = "{'a':1,\"b\":tru,\"c\":2}";
+    let opts = qjson::Options {
+        lenient: true,
+        ..Default::default()
+    };
+    assert_eq!(qjson::validate_all::<1>(src, &mut errs), 2);
+    assert_eq!(qjson::validate_all_with::<1>(src, &mut errs, opts), 1);
+    assert_eq!(errs[0].kind(), qjson::ErrorKind::UnknownIdentifier);
+    let opts = qjson::Options {
+        allow_empty: true,
+        ..Default::default()
+    };
+    assert_eq!(qjson::validate_all_with::<1>(" ", &mut errs, opts), 0);
+}
+
+#[test]
+fn ok_schema_display() {
+    let (mut name, mut ids, mut on) = (None, [None, None], None);
+    let mut kind = None;
+    let [id0, id1] = &mut ids;
+    let mut ids_desc = [qjson::Schema::Integer(id0), qjson::Schema::Integer(id1)];
+    let mut empty: [qjson::Schema; 0] = [];
+    let mut desc = [
+        ("name", qjson::Schema::Str(&mut name)),
+        ("ids", qjson::Schema::Array(&mut ids_desc)),
+        ("on", qjson::Schema::Bool(&mut on)),
+        ("none", qjson::Schema::Array(&mut empty)),
+        (
+            "kind",
+            qjson::Schema::Enum {
+                out: &mut kind,
+                variants: &["a", "b"],
+            },
+        ),
+    ];
+    let mut schema = qjson::Schema::from(&mut desc);
+    let src = r#"{"name":"x","ids":[1],"kind":"b"}"#;
+    qjson::from_str_mut::<2>(src, &mut schema).unwrap();
+
+    let expected = r#"{
+  "name": "x",
+  "ids": [
+    1,
+    null
+  ],
+  "on": null,
+  "none": [],
+  "kind": "b"
+}"#;
+    assert_eq!(schema.display().to_string(), expected);
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Sensor<'a> {
+    name: Option<&'a str>,
+    ids: [Option<i64>; 2],
+    on: Option<bool>,
+    kind: Option<usize>,
+    perms: Option<u64>,
+    floats: [Option<f64>; 4],
+    samples: [i64; 3],
+    gains: [f64; 2],
+    extremes: (Option<u64>, Option<i8>),
+    unset: Option<u8>,
+}
+
+#[test]
+fn ok_to_writer_roundtrip() {
+    let mut sensor = Sensor {
+        name: Some("tab\t\"quote\" \\ é 😀 \u{1}"),
+        ids: [Some(-1), None],
+        on: Some(true),
+        kind: Some(1),
+        perms: Some(0b101),
+        floats: [Some(0.1), Some(1e300), Some(5e-324), Some(-0.0)],
+        samples: [1, -2, 3],
+        gains: [1.5, -2.0],
+        extremes: (Some(u64::MAX), Some(i8::MIN)),
+        unset: None,
+    };
+
+    let mut out = String::new();
+    {
+        let Sensor {
+            name,
+            ids: [id0, id1],
+            on,
+            kind,
+            perms,
+            floats: [f0, f1, f2, f3],
+            samples,
+            gains,
+            extremes: (max, min),
+            unset,
+        } = &mut sensor;
+        let mut desc = qjson::schema!({
+            "name": Str(name),
+            "ids": [Integer(id0), Integer(id1)],
+            "nested": { "on": Bool(on), "kind": Enum { out: kind, variants: &["a", "b"] } },
+            "perms": Flags { out: perms, names: PERMS },
+            "floats": [Float(f0), Float(f1), Float(f2), Float(f3)],
+            "samples": IntArray(samples),
+            "gains": FloatArray(gains),
+            "max": U64(max),
+            "min": I8(min),
+            "unset": U8(unset),
+        });
+        qjson::to_writer(&mut out, &mut desc).unwrap();
+    }
+    assert!(out.starts_with(r#"{"name":"tab\t\"quote\" \\ é 😀 \u0001","ids":[-1,null],"#));
+
+    let mut buf = [0; 64];
+    let mut parsed = Sensor::default();
+    {
+        let Sensor {
+            name,
+            ids: [id0, id1],
+            on,
+            kind,
+            perms,
+            floats: [f0, f1, f2, f3],
+            samples,
+            gains,
+            extremes: (max, min),
+            unset,
+        } = &mut parsed;
+        let mut desc = qjson::schema!({
+            "name": StrBuf(&mut buf, name),
+            "ids": [Integer(id0), Integer(id1)],
+            "nested": { "on": Bool(on), "kind": Enum { out: kind, variants: &["a", "b"] } },
+            "perms": Flags { out: perms, names: PERMS },
+            "floats": [Float(f0), Float(f1), Float(f2), Float(f3)],
+            "samples": IntArray(samples),
+            "gains": FloatArray(gains),
+            "max": U64(max),
+            "min": I8(min),
+            "unset": U8(unset),
+        });
+        qjson::from_str::<_, 2>(&out, &mut desc).unwrap();
+    }
+    assert_eq!(parsed, sensor);
+    assert!(parsed.floats[3].unwrap().is_sign_negative());
+}
+
+#[test]
+fn ok_to_writer_float_shortest() {
+    let write = |f: f64| {
+        let mut out = String::new();
+        qjson::to_writer(&mut out, qjson::Schema::Float(&mut Some(f))).unwrap();
+        out
+    };
+
+    let edges = [
+        0.1,
+        0.3,
+        1.0,
+        -2.5,
+        123.456,
+        1e-4,
+        1.5e-5,
+        1e15,
+        1e16,
+        9007199254740992.0,
+        f64::MIN_POSITIVE,
+        f64::MAX,
+        f64::MIN,
+        f64::EPSILON,
+        5e-324,
+        2.225073858507201e-308,
+    ];
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let random = std::iter::repeat_with(|| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        f64::from_bits(state)
+    });
+    // powers of two, normal and subnormal
+    let powers = (1..2047).map(|e| e << 52).chain((0..52).map(|i| 1 << i));
+    let powers = powers.map(f64::from_bits);
+    for f in edges.into_iter().chain(powers).chain(random.take(20_000)) {
+        if f.is_finite() {
+            assert_eq!(write(f), format!("{:?}", f));
+        }
+    }
+}
+
+#[test]
+fn ok_to_writer_verbatim() {
+    let (mut raw, mut nan, mut present) = (Some("[1, 2]"), Some(f64::NAN), false);
+    let mut fields: [(&str, qjson::Schema); 0] = [];
+    let mut desc = [
+        ("raw", qjson::Schema::Raw(&mut raw)),
+        ("nan", qjson::Schema::Float(&mut nan)),
+        (
+            "opt",
+            qjson::Schema::OptionalObject {
+                present: &mut present,
+                fields: &mut fields,
+            },
+        ),
+    ];
+
+    let mut out = String::new();
+    qjson::to_writer(&mut out, &mut desc).unwrap();
+    assert_eq!(out, r#"{"raw":[1, 2],"nan":null,"opt":null}"#);
+}
+
+#[test]
+fn ok_to_writer_escaped_keys() {
+    let src = r#"{"a\"b":1,"c\\d":{"e\u0066":[true]}}"#;
+    let mut pairs = [("", ""); 2];
+    let mut count = 0;
+    let desc = qjson::Schema::ObjectCapture(&mut pairs, &mut count);
+    qjson::from_str::<_, 3>(src, desc).unwrap();
+
+    let mut out = String::new();
+    let desc = qjson::Schema::ObjectCapture(&mut pairs, &mut count);
+    qjson::to_writer(&mut out, desc).unwrap();
+    assert_eq!(out, src);
+
+    // schema keys and names are matched against the source text as is
+    let src = r#"{"a\"b":1,"k":"x\ny"}"#;
+    for write in [true, false] {
+        let (mut n, mut kind) = if write {
+            (Some(1), Some(0))
+        } else {
+            (None, None)
+        };
+        {
+            let mut desc = [
+                ("a\\\"b", qjson::Schema::Integer(&mut n)),
+                (
+                    "k",
+                    qjson::Schema::Enum {
+                        out: &mut kind,
+                        variants: &["x\\ny"],
+                    },
+                ),
+            ];
+            if write {
+                let mut out = String::new();
+                qjson::to_writer(&mut out, &mut desc).unwrap();
+                assert_eq!(out, src);
+            } else {
+                qjson::from_str::<_, 1>(src, &mut desc).unwrap();
+            }
+        }
+        assert_eq!((n, kind), (Some(1), Some(0)));
+    }
+}
+
+#[test]
+fn err_best_effort() {
+    let (mut a, mut ids, mut flags, mut c) = (None, [None, None, None], None, None);
+    let [id0, id1, id2] = &mut ids;
+    let mut ids_desc = [
+        qjson::Schema::Integer(id0),
+        qjson::Schema::Integer(id1),
+        qjson::Schema::Integer(id2),
+    ];
+    let mut desc = [
+        ("a", qjson::Schema::Str(&mut a)),
+        ("ids", qjson::Schema::Array(&mut ids_desc)),
+        (
+            "flags",
+            qjson::Schema::Flags {
+                out: &mut flags,
+                names: &[("x", 1), ("y", 2)],
+            },
+        ),
+        ("c", qjson::Schema::Integer(&mut c)),
+    ];
+    let src = r#"{"a":"x","ids":[1,2],"flags":["x","z"],"c":3}"#;
+    let (count, err) = qjson::from_str_best_effort::<_, 2>(src, &mut desc);
+
+    assert_eq!(err.unwrap().kind(), qjson::ErrorKind::UnknownVariant);
+    assert_eq!(count, 3);
+    assert_eq!(a, Some("x"));
+    assert_eq!(ids, [Some(1), Some(2), None]);
+    assert_eq!(flags, None);
+    assert_eq!(c, None);
+}
+
+#[test]
+fn err_best_effort_object_capture() {
+    let mut pairs = [("", ""); 4];
+    let mut count = 0;
+    let src = r#"{"a":1,"b":[2],"c":}"#;
+    let (n, err) = qjson::from_str_best_effort::<_, 2>(
+        src,
+        qjson::Schema::ObjectCapture(&mut pairs, &mut count),
+    );
+
+    assert_eq!(err.unwrap().kind(), qjson::ErrorKind::UnexpectedToken);
+    assert_eq!(n, 2);
+    assert_eq!(count, 2);
+    assert_eq!(pairs[..2], [("a", "1"), ("b", "[2]")]);
+}
+
+fn bind_stack_or_recursive<'a>(
+    src: &'a str,
+    stack: Option<&mut [qjson::Frame<'a>]>,
+) -> (String, bool) {
+    let (mut name, mut ids, mut nested, mut flags) = (None, [None, None], None, None);
+    let (mut on, mut n, mut alias, mut matched) = (None, Some(9), None, None);
+    let mut pairs = [("", ""); 2];
+    let mut count = 0;
+    let [id0, id2] = &mut ids;
+    let mut skip_desc: [(&str, qjson::Schema); 0] = [];
+    let mut nested_desc = [
+        qjson::Schema::Integer(&mut nested),
+        qjson::Schema::Object(&mut skip_desc),
+    ];
+    let mut ids_desc = [
+        qjson::Schema::Integer(id0),
+        qjson::Schema::Array(&mut nested_desc),
+        qjson::Schema::Integer(id2),
+    ];
+    let mut inner_desc = [
+        ("on", qjson::Schema::Bool(&mut on)),
+        ("n", qjson::Schema::Integer(&mut n)),
+    ];
+    let mut alias_desc = [(
+        &["a", "b"][..],
+        &mut matched,
+        qjson::Schema::Integer(&mut alias),
+    )];
+    let mut desc = [
+        ("name", qjson::Schema::Str(&mut name)),
+        ("ids", qjson::Schema::Array(&mut ids_desc)),
+        (
+            "flags",
+            qjson::Schema::Flags {
+                out: &mut flags,
+                names: &[("a", 1), ("b", 4)],
+            },
+        ),
+        ("inner", qjson::Schema::Object(&mut inner_desc)),
+        ("alias", qjson::Schema::ObjectKeys(&mut alias_desc)),
+        ("raw", qjson::Schema::ObjectCapture(&mut pairs, &mut count)),
+    ];
+    let ok = match stack {
+        Some(stack) => qjson::from_str_stack(src, &mut desc, stack).is_ok(),
+        None => qjson::from_str::<_, 5>(src, &mut desc).is_ok(),
+    };
+    let out = format!(
+        "{:?}",
+        (name, ids, nested, flags, on, n, alias, matched, pairs, count)
+    );
+    (out, ok)
+}
+
+#[test]
+fn ok_stack_matches_recursive() {
+    let src = r#"{
+        "name": "x",
+        "ids": [1, [2, {"skip": [3]}], 4],
+        "flags": ["a", "b"],
+        "inner": {"on": true, "unused": {}, "n": null},
+        "alias": {"b": 5},
+        "raw": {"k": [1, {"x": 2}], "s": "v"}
+    }"#;
+
+    let mut stack = [qjson::Frame::new(); 5];
+    let (recursive, ok) = bind_stack_or_recursive(src, None);
+    assert!(ok);
+    assert_eq!(
+        bind_stack_or_recursive(src, Some(&mut stack)),
+        (recursive.clone(), true)
+    );
+    assert_eq!(
+        recursive,
+        format!(
+            "{:?}",
+            (
+                Some("x"),
+                [Some(1), Some(4)],
+                Some(2),
+                Some(5),
+                Some(true),
+                None::<i64>,
+                Some(5),
+                Some(1),
+                [("k", r#"[1, {"x": 2}]"#), ("s", r#""v""#)],
+                2
+            )
+        )
+    );
+
+    let mut stack = [qjson::Frame::new(); 4];
+    assert!(!bind_stack_or_recursive(src, Some(&mut stack)).1);
+}
+
+#[test]
+fn err_stack() {
+    let mut stack = [qjson::Frame::new(); 2];
+    let err = qjson::validate_stack("[[1,[2,{}]]]", &mut stack).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MaxDepthExceeded);
+    assert_eq!(err.depth(), Some(4));
+    assert_eq!(err.col(), 5);
+
+    let mut stack = [qjson::Frame::new(); 4];
+    qjson::validate_stack("[[1,[2,{}]]]", &mut stack).unwrap();
+
+    let err = qjson::validate_stack(r#"{"a":[1,]}"#, &mut stack).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedTrailingComma);
+    let err = qjson::validate_stack("[1] 2", &mut stack).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::UnexpectedToken);
+
+    let mut a = [None];
+    let [a0] = &mut a;
+    let mut desc = [qjson::Schema::Integer(a0)];
+    let err = qjson::from_str_stack("[1,2]", &mut desc, &mut stack).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::InsufficientArrayLength);
+
+    let mut i: Option<i64> = None;
+    let err = qjson::from_str_stack("[1]", &mut i, &mut stack).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedTypes);
+}
+
+#[test]
+fn ok_stack_deep() {
+    const DEPTH: usize = 100_000;
+    let src = "[".repeat(DEPTH) + &"]".repeat(DEPTH);
+    let mut stack = vec![qjson::Frame::new(); DEPTH];
+    qjson::validate_stack(&src, &mut stack).unwrap();
+    let err = qjson::validate_stack(&src, &mut stack[1..]).unwrap_err();
+    assert_eq!(err.depth(), Some(DEPTH));
+}
+
+#[test]
+fn ok_indexed_object() {
+    let mut slots = [None, None, None];
+    let [s0, s1, s2] = &mut slots;
+    let mut slots_desc = [
+        qjson::Schema::Integer(s0),
+        qjson::Schema::Integer(s1),
+        qjson::Schema::Integer(s2),
+    ];
+    let mut desc = [("sparse", qjson::Schema::IndexedObject(&mut slots_desc))];
+    let src = r#"{"sparse":{"2":5,"0":1}}"#;
+    qjson::from_str::<_, 2>(src, &mut desc).unwrap();
+    assert_eq!(slots, [Some(1), None, Some(5)]);
+}
+
+#[test]
+fn ok_indexed_object_stack() {
+    let mut slots = [None, None];
+    let [s0, s1] = &mut slots;
+    let mut desc = [qjson::Schema::Str(s0), qjson::Schema::Str(s1)];
+    let mut stack = [qjson::Frame::new(); 1];
+    let src = r#"{"1":"b","0":"a"}"#;
+    qjson::from_str_stack(src, qjson::Schema::IndexedObject(&mut desc), &mut stack).unwrap();
+    assert_eq!(slots, [Some("a"), Some("b")]);
+}
+
+#[test]
+fn err_indexed_object() {
+    for (src, kind) in [
+        (r#"{"x":1}"#, qjson::ErrorKind::MismatchedTypes),
+        (r#"{"1.0":1}"#, qjson::ErrorKind::MismatchedTypes),
+        (r#"{"01":1}"#, qjson::ErrorKind::MismatchedTypes),
+        (r#"{"-1":1}"#, qjson::ErrorKind::MismatchedTypes),
+        (r#"{"2":1}"#, qjson::ErrorKind::InsufficientArrayLength),
+        (
+            r#"{"99999999999999999999999":1}"#,
+            qjson::ErrorKind::InsufficientArrayLength,
+        ),
+    ] {
+        let mut slots = [None, None];
+        let [s0, s1] = &mut slots;
+        let mut desc = [qjson::Schema::Integer(s0), qjson::Schema::Integer(s1)];
+        let err =
+            qjson::from_str::<_, 1>(src, qjson::Schema::IndexedObject(&mut desc)).unwrap_err();
+        assert_eq!(err.kind(), kind, "{}", src);
+
+        let mut desc = [
+            qjson::Schema::Integer(&mut None),
+            qjson::Schema::Integer(&mut None),
+        ];
+        let mut stack = [qjson::Frame::new(); 1];
+        let err = qjson::from_str_stack(src, qjson::Schema::IndexedObject(&mut desc), &mut stack)
+            .unwrap_err();
+        assert_eq!(err.kind(), kind, "{}", src);
+    }
+}
+
+#[test]
+fn ok_int_and_float_array() {
+    let mut ts = [0; 6];
+    let mut samples = [0.0; 3];
+    let mut desc = [
+        ("ts", qjson::Schema::IntArray(&mut ts)),
+        ("samples", qjson::Schema::FloatArray(&mut samples)),
+    ];
+    let src = r#"{"ts":[2024,1,31,23,59,-1],"samples":[1.5,-2,0.25]}"#;
+    assert_eq!(qjson::from_str_count::<_, 2>(src, &mut desc).unwrap(), 9);
+    assert_eq!(ts, [2024, 1, 31, 23, 59, -1]);
+    assert_eq!(samples, [1.5, -2.0, 0.25]);
+
+    let mut empty: [i64; 0] = [];
+    qjson::from_str::<_, 1>("[]", qjson::Schema::IntArray(&mut empty)).unwrap();
+
+    let mut stack = [qjson::Frame::new(); 1];
+    let mut ts = [0; 2];
+    let mut desc = [("ts", qjson::Schema::IntArray(&mut ts))];
+    qjson::from_str_stack(r#"{"ts":[1,2]}"#, &mut desc, &mut stack).unwrap();
+    assert_eq!(ts, [1, 2]);
+}
+
+#[test]
+fn err_int_array() {
+    for (src, kind) in [
+        ("[1,2.5,3]", qjson::ErrorKind::MismatchedTypes),
+        (r#"[1,"2",3]"#, qjson::ErrorKind::MismatchedTypes),
+        ("[1,2]", qjson::ErrorKind::InsufficientArrayLength),
+        ("[1,2,3,4]", qjson::ErrorKind::InsufficientArrayLength),
+        (
+            "[1,2,99999999999999999999]",
+            qjson::ErrorKind::NumberOutOfRange,
+        ),
+        ("[1,2,3,]", qjson::ErrorKind::UnexpectedTrailingComma),
+        ("[1,,3]", qjson::ErrorKind::UnexpectedToken),
+        ("{}", qjson::ErrorKind::MismatchedTypes),
+    ] {
+        let mut out = [0; 3];
+        let err = qjson::from_str::<_, 1>(src, qjson::Schema::IntArray(&mut out)).unwrap_err();
+        assert_eq!(err.kind(), kind, "{}", src);
+    }
+}
+
+#[test]
+fn err_tagged() {
+    for (src, kind, col) in [
+        (
+            r#"{"type": "pressure", "value": 1}"#,
+            qjson::ErrorKind::UnknownIdentifier,
+            10,
+        ),
+        (r#"{"value": 1}"#, qjson::ErrorKind::UnknownIdentifier, 12),
+        (r#"{"type": 1}"#, qjson::ErrorKind::MismatchedTypes, 10),
+    ] {
+        let mut value = None;
+        let mut arm = None;
+        let mut temperature = [("value", qjson::Schema::Float(&mut value))];
+        let mut arms = [("temperature", &mut temperature[..])];
+        let desc = qjson::Schema::Tagged {
+            tag: "type",
+            out: &mut arm,
+            arms: &mut arms,
+        };
+        let err = qjson::from_str::<_, 2>(src, desc).unwrap_err();
+        assert_eq!(err.kind(), kind, "{src}");
+        assert_eq!((err.lineno(), err.col()), (1, col), "{src}");
+        assert_eq!(arm, None);
+    }
+}
+
+#[test]
+fn err_mismatched_bracket() {
+    for (src, col) in [
+        (r#"{"a":[}"#, 7),
+        (r#"{"a":[1}"#, 8),
+        (r#"{"a":1]"#, 7),
+        (r#"{"a":{"b":1]}"#, 12),
+        ("[}", 2),
+        ("[1,}", 4),
+    ] {
+        let err = qjson::validate::<3>(src).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::MismatchedBracket, "{src}");
+        assert_eq!((err.lineno(), err.col()), (1, col), "{src}");
+
+        if !src.starts_with('{') {
+            continue;
+        }
+        let mut x = None;
+        let mut desc = [("x", qjson::Schema::Integer(&mut x))];
+        let mut stack = [qjson::Frame::new(); 3];
+        let err = qjson::from_str_stack(src, &mut desc, &mut stack).unwrap_err();
+        assert_eq!(err.kind(), qjson::ErrorKind::MismatchedBracket, "{src}");
+        assert_eq!((err.lineno(), err.col()), (1, col), "{src}");
+    }
+
+    let mut a = [0; 2];
+    let err = qjson::from_str::<_, 1>("[1,2}", qjson::Schema::IntArray(&mut a)).unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::MismatchedBracket);
+    assert_eq!((err.lineno(), err.col()), (1, 5));
+}
+
+#[test]
+fn ok_tokens() {
+    use qjson::Token::*;
+
+    let src = "{\"s\": \"x\\\"y\",\n \"n\": -1.5e3, \"b\": [false, null]}";
+    let tokens = qjson::tokens(src)
+        .map(|t| {
+            let (tok, span) = t.unwrap();
+            (tok, &src[span.start..span.end], (span.lineno, span.col))
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        tokens,
+        [
+            (BraceL, "{", (1, 1)),
+            (
+                Str {
+                    text: "s",
+                    escaped: false
+                },
+                r#""s""#,
+                (1, 2)
+            ),
+            (Colon, ":", (1, 5)),
+            (
+                Str {
+                    text: r#"x\"y"#,
+                    escaped: true
+                },
+                r#""x\"y""#,
+                (1, 7)
+            ),
+            (Comma, ",", (1, 13)),
+            (
+                Str {
+                    text: "n",
+                    escaped: false
+                },
+                r#""n""#,
+                (2, 2)
+            ),
+            (Colon, ":", (2, 5)),
+            (Number("-1.5e3"), "-1.5e3", (2, 7)),
+            (Comma, ",", (2, 13)),
+            (
+                Str {
+                    text: "b",
+                    escaped: false
+                },
+                r#""b""#,
+                (2, 15)
+            ),
+            (Colon, ":", (2, 18)),
+            (BracketL, "[", (2, 20)),
+            (Bool(false), "false", (2, 21)),
+            (Comma, ",", (2, 26)),
+            (Null, "null", (2, 28)),
+            (BracketR, "]", (2, 32)),
+            (BraceR, "}", (2, 33)),
+        ]
+    );
+
+    // tokens are not checked for how they nest
+    assert_eq!(qjson::tokens("]] 1 2").count(), 4);
+
+    let opts = qjson::Options {
+        lenient: true,
+        ..Default::default()
+    };
+    let (tok, span) = qjson::tokens_with("\x0C'a'", opts).next().unwrap().unwrap();
+    assert_eq!(
+        (tok, span.start, span.end),
+        (
+            Str {
+                text: "a",
+                escaped: false
+            },
+            1,
+            4
+        )
+    );
+}
+
+#[test]
+fn err_tokens() {
+    let mut tokens = qjson::tokens("[1, 01, 2]");
+    assert!(tokens.next().unwrap().is_ok());
+    assert!(tokens.next().unwrap().is_ok());
+    assert!(tokens.next().unwrap().is_ok());
+    let err = tokens.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), qjson::ErrorKind::InvalidNumber);
+    assert!(tokens.next().is_none());
+}
@@ -30,9 +30,39 @@
 //! # Pointer Creation
 //!
 //! Owned pointers can be created using the [`make_static_shared`] and
-//! [`make_static_unique`] macros. These macros return an option — as the
-//! macros back the pointers with static data they cannot be called in a
-//! loop!
+//! [`make_static_unique`] macros. These macros return an option — the
+//! macros back the pointers with static data, which is held for as long as
+//! a [`Unique`] is live and forever by a [`Shared`], and in the meantime
+//! they return `None`.
+//!
+//! Dropping a [`Unique`] drops the data behind it and releases its static
+//! data, so the macro which created it can then be called again:
+//!
+//! ```
+//! use qptr::{make_static_unique, Unique};
+//!
+//! let make = || make_static_unique!(|| -> i32 { 123 });
+//!
+//! let first: Unique<i32> = make().unwrap();
+//! assert!(make().is_none());
+//!
+//! drop(first);
+//! assert!(make().is_some());
+//! ```
+//!
+//! A value which has already been built can be moved into a [`StaticSlot`]
+//! declared by the caller instead, with [`Unique::try_new`] or
+//! [`Shared::try_new`]:
+//!
+//! ```
+//! use qptr::{StaticSlot, Unique};
+//!
+//! static SLOT: StaticSlot<[u8; 4]> = StaticSlot::new();
+//!
+//! let value = [1, 2, 3, 4];
+//! let boxed = Unique::try_new(&SLOT, value).unwrap();
+//! assert!(Unique::try_new(&SLOT, value).is_none());
+//! ```
 //!
 //! # Dynamic Dispatch
 //!
@@ -59,23 +89,52 @@
 //! let boxed: Unique<[u8]> = make_static_unique!(|| -> [u8; 3] { [1, 2, 3] }).unwrap();
 //! ```
 //!
+//! Large slices can be filled element-by-element from an iterator with the
+//! [`make_static_unique_from_iter`] macro, which avoids building the whole
+//! array on the stack first:
+//!
+//! ```
+//! use qptr::{make_static_unique_from_iter, Unique};
+//!
+//! let boxed: Unique<[u8]> = make_static_unique_from_iter!([u8; 1024], 0..3).unwrap();
+//! assert_eq!(&*boxed, &[0, 1, 2]);
+//! ```
+//!
+//! Zero-initialized buffers can be claimed with the
+//! [`make_static_unique_zeroed`] macro, which uses the static data as it is
+//! rather than copying a zeroed array into it:
+//!
+//! ```
+//! use qptr::{make_static_unique_zeroed, Unique};
+//!
+//! let boxed: Unique<[u8]> = make_static_unique_zeroed!([u8; 4096]).unwrap();
+//! assert!(boxed.iter().all(|b| *b == 0));
+//! ```
+//!
 //! [`Shared`]: struct.Shared.html
 //! [`Unique`]: struct.Unique.html
 //! [`Arc`]: https://doc.rust-lang.org/stable/alloc/sync/struct.Arc.html
 //! [`Box`]: https://doc.rust-lang.org/stable/alloc/boxed/struct.Box.html
 //! [`make_static_shared`]: macro.make_static_shared.html
 //! [`make_static_unique`]: macro.make_static_unique.html
+//! [`make_static_unique_from_iter`]: macro.make_static_unique_from_iter.html
+//! [`make_static_unique_zeroed`]: macro.make_static_unique_zeroed.html
+//! [`StaticSlot`]: struct.StaticSlot.html
+//! [`Unique::try_new`]: struct.Unique.html#method.try_new
+//! [`Shared::try_new`]: struct.Shared.html#method.try_new
 
 #![no_std]
 
 use core::any::Any;
+use core::cell::UnsafeCell;
 use core::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 use core::fmt::{self, Debug, Display, Formatter};
 use core::hash::{Hash, Hasher};
 use core::marker::Unpin;
-use core::mem;
+use core::mem::{self, MaybeUninit};
 use core::ops::{Deref, DerefMut};
 use core::ptr;
+use core::sync::atomic::{self, AtomicBool};
 
 /// A shared owned pointer.
 ///
@@ -90,20 +149,56 @@ pub struct Shared<T: ?Sized> {
 
 /// A unique owned pointer.
 ///
-/// Create instances with the [`make_static_unique`] macro.
+/// Create instances with the [`make_static_unique`] macro. Dropping the
+/// pointer drops the data behind it and allows the macro to be called again.
 ///
 /// For more information please consult the crate level documentation.
 ///
 /// [`make_static_unique`]: macro.make_static_unique.html
 pub struct Unique<T: ?Sized> {
     ptr: *mut T,
+    claimed: &'static AtomicBool,
 }
 
+/// Static storage for a single pointer, declared by the caller.
+///
+/// Generic statics cannot be declared inside a function, so this allows a
+/// pointer to be created from an existing value with [`Unique::try_new`] or
+/// [`Shared::try_new`] rather than the creation macros.
+///
+/// [`Unique::try_new`]: struct.Unique.html#method.try_new
+/// [`Shared::try_new`]: struct.Shared.html#method.try_new
+pub struct StaticSlot<T> {
+    obj: UnsafeCell<MaybeUninit<T>>,
+    claimed: AtomicBool,
+}
+
+/// Types which are valid when every byte is zero.
+///
+/// This bounds the element type of [`make_static_unique_zeroed`].
+///
+/// # Safety
+///
+/// An all-zero bit pattern must be a valid value of the type.
+///
+/// [`make_static_unique_zeroed`]: macro.make_static_unique_zeroed.html
+pub unsafe trait Zeroable {}
+
+macro_rules! impl_zeroable {
+    ($($ty:ty),*) => {
+        $(unsafe impl Zeroable for $ty {})*
+    };
+}
+
+impl_zeroable!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool);
+
+unsafe impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
+
 macro_rules! static_creation {
-    ($name:ident, $kind:ty, $kind_str:literal) => {
+    ($name:ident, $kind:ident, $kind_str:literal) => {
         static_creation! { $name, $kind, $kind_str, $ }
     };
-    ($name:ident, $kind:ty, $kind_str:literal, $d:tt) => {
+    ($name:ident, $kind:ident, $kind_str:literal, $d:tt) => {
         #[doc = concat!("Safely creates a ", $kind_str, " pointer using static data")]
         #[doc = ""]
         #[doc = "# Example"]
@@ -144,11 +239,11 @@ macro_rules! static_creation {
                 static OBJ_CLAIMED: AtomicBool = AtomicBool::new(false);
 
                 let buf = OBJ.get() as *mut u8;
-                if !OBJ_CLAIMED.swap(true, Ordering::Relaxed) {
+                if !OBJ_CLAIMED.swap(true, Ordering::Acquire) {
                     let mut val: $d ty = { $d($d arg)+ };
                     let val_ptr = &mut val as *mut _;
                     #[allow(unused_unsafe)]
-                    let obj = unsafe { $kind::new(buf, &mut val, val_ptr) };
+                    let obj = unsafe { $kind::new(buf, &mut val, val_ptr, &OBJ_CLAIMED) };
                     mem::forget(val);
                     Some(obj)
 
@@ -164,23 +259,199 @@ macro_rules! static_creation {
 static_creation!(make_static_shared, Shared, "shared");
 static_creation!(make_static_unique, Unique, "unique");
 
+/// Safely creates a unique slice pointer using static data, filling it from
+/// an iterator
+///
+/// The static data is sized to hold `N` elements but the returned slice only
+/// covers the elements the iterator produced. Any elements past `N` are not
+/// pulled from the iterator.
+///
+/// # Example
+///
+/// ```
+/// use qptr::{make_static_unique_from_iter, Unique};
+///
+/// let x: Unique<[u32]> = make_static_unique_from_iter!([u32; 8], 1..=3).unwrap();
+/// assert_eq!(x.len(), 3);
+/// ```
+#[macro_export]
+macro_rules! make_static_unique_from_iter {
+    ([$ty:ty; $n:expr], $iter:expr) => {{
+        use ::core::cell::UnsafeCell;
+        use ::core::mem::MaybeUninit;
+        use ::core::sync::atomic::{AtomicBool, Ordering};
+
+        #[repr(transparent)]
+        struct Obj<T, const N: usize>(UnsafeCell<MaybeUninit<[T; N]>>);
+
+        impl<T, const N: usize> Obj<T, N> {
+            #[inline(always)]
+            pub const fn new() -> Self {
+                Self(UnsafeCell::new(MaybeUninit::uninit()))
+            }
+
+            #[inline(always)]
+            pub fn get(&self) -> *mut T {
+                unsafe { (&mut *self.0.get()).as_mut_ptr() as *mut T }
+            }
+        }
+
+        unsafe impl<T, const N: usize> Sync for Obj<T, N> {}
+
+        static OBJ: Obj<$ty, { $n }> = Obj::new();
+        static OBJ_CLAIMED: AtomicBool = AtomicBool::new(false);
+
+        if !OBJ_CLAIMED.swap(true, Ordering::Acquire) {
+            #[allow(unused_unsafe)]
+            let obj = unsafe {
+                $crate::Unique::<[$ty]>::new_from_iter(OBJ.get(), $n, $iter, &OBJ_CLAIMED)
+            };
+            Some(obj)
+
+        // already claimed from static memory
+        } else {
+            None
+        }
+    }};
+}
+
+/// Safely creates a zeroed unique slice pointer using static data
+///
+/// The static data starts out zeroed, so no array is built on the stack and
+/// nothing is written when it is first claimed. Static data which is claimed
+/// again after its pointer was dropped is zeroed in place.
+///
+/// The element type must implement [`Zeroable`].
+///
+/// # Example
+///
+/// ```
+/// use qptr::{make_static_unique_zeroed, Unique};
+///
+/// let x: Unique<[u32]> = make_static_unique_zeroed!([u32; 8]).unwrap();
+/// assert_eq!(&*x, &[0; 8]);
+/// ```
+///
+/// [`Zeroable`]: trait.Zeroable.html
+#[macro_export]
+macro_rules! make_static_unique_zeroed {
+    ([$ty:ty; $n:expr]) => {{
+        use ::core::cell::UnsafeCell;
+        use ::core::mem::MaybeUninit;
+        use ::core::sync::atomic::{AtomicBool, Ordering};
+
+        #[repr(transparent)]
+        struct Obj<T, const N: usize>(UnsafeCell<MaybeUninit<[T; N]>>);
+
+        impl<T, const N: usize> Obj<T, N> {
+            #[inline(always)]
+            pub const fn new() -> Self {
+                Self(UnsafeCell::new(MaybeUninit::zeroed()))
+            }
+
+            #[inline(always)]
+            pub fn get(&self) -> *mut T {
+                unsafe { (&mut *self.0.get()).as_mut_ptr() as *mut T }
+            }
+        }
+
+        unsafe impl<T, const N: usize> Sync for Obj<T, N> {}
+
+        static OBJ: Obj<$ty, { $n }> = Obj::new();
+        static OBJ_CLAIMED: AtomicBool = AtomicBool::new(false);
+        static OBJ_USED: AtomicBool = AtomicBool::new(false);
+
+        if !OBJ_CLAIMED.swap(true, Ordering::Acquire) {
+            // a previous pointer may have written to the static data
+            let dirty = OBJ_USED.swap(true, Ordering::Relaxed);
+            #[allow(unused_unsafe)]
+            let obj =
+                unsafe { $crate::Unique::<[$ty]>::new_zeroed(OBJ.get(), $n, dirty, &OBJ_CLAIMED) };
+            Some(obj)
+
+        // already claimed from static memory
+        } else {
+            None
+        }
+    }};
+}
+
 unsafe fn create_obj<T: ?Sized, U>(buf: *mut u8, val: &mut U, mut val_ptr: *mut T) -> *mut T {
-    ptr::copy_nonoverlapping(
-        val as *const _ as *const u8,
-        buf,
-        mem::size_of_val::<U>(&val),
-    );
+    // the static buffer and `val` are both typed by the return annotation of
+    // the creation macro, so the buffer always fits the value behind the
+    // (possibly fat) pointer
+    ptr::copy_nonoverlapping(val as *const U as *const u8, buf, mem::size_of::<U>());
 
     let target = &mut val_ptr as *mut *mut T as *mut *mut u8;
     *target = buf;
     val_ptr
 }
 
+// impl StaticSlot
+
+impl<T> StaticSlot<T> {
+    /// Creates empty static storage.
+    pub const fn new() -> Self {
+        Self {
+            obj: UnsafeCell::new(MaybeUninit::uninit()),
+            claimed: AtomicBool::new(false),
+        }
+    }
+
+    /// Moves `value` into the slot, returning a pointer to it, or `None` if
+    /// the slot is already claimed.
+    fn claim(&'static self, value: T) -> Option<*mut T> {
+        if self.claimed.swap(true, atomic::Ordering::Acquire) {
+            return None;
+        }
+        let ptr = self.obj.get().cast::<T>();
+        // safety: the slot was unclaimed, so nothing else can access it
+        unsafe { ptr.write(value) };
+        Some(ptr)
+    }
+}
+
+impl<T> Default for StaticSlot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<T: Send> Sync for StaticSlot<T> {}
+
 // impl Shared
 
+impl<T> Shared<T> {
+    /// Creates a shared pointer by moving `value` into static storage.
+    ///
+    /// Returns `None` (dropping `value`) if the slot is already claimed.
+    /// Shared pointers are never released, so a slot can only be claimed
+    /// once.
+    ///
+    /// ```
+    /// use qptr::{Shared, StaticSlot};
+    ///
+    /// static SLOT: StaticSlot<i32> = StaticSlot::new();
+    ///
+    /// let x = Shared::try_new(&SLOT, 123).unwrap();
+    /// assert_eq!(*Shared::clone(&x), 123);
+    /// ```
+    pub fn try_new(slot: &'static StaticSlot<T>, value: T) -> Option<Self> {
+        Some(Self {
+            ptr: slot.claim(value)?,
+        })
+    }
+}
+
 impl<T: ?Sized> Shared<T> {
+    // shared pointers are never released, so the claimed flag is unused
     #[doc(hidden)]
-    pub unsafe fn new<U>(buf: *mut u8, val: &mut U, val_ptr: *mut T) -> Self {
+    pub unsafe fn new<U>(
+        buf: *mut u8,
+        val: &mut U,
+        val_ptr: *mut T,
+        _claimed: &'static AtomicBool,
+    ) -> Self {
         Self {
             ptr: create_obj(buf, val, val_ptr),
         }
@@ -303,11 +574,83 @@ impl<T: ?Sized> Unpin for Shared<T> {}
 
 // impl Unique
 
+impl<T> Unique<T> {
+    /// Creates a unique pointer by moving `value` into static storage.
+    ///
+    /// Returns `None` (dropping `value`) if the slot is already claimed.
+    /// Dropping the pointer releases the slot again.
+    ///
+    /// ```
+    /// use qptr::{StaticSlot, Unique};
+    ///
+    /// static SLOT: StaticSlot<i32> = StaticSlot::new();
+    ///
+    /// let mut x = Unique::try_new(&SLOT, 123).unwrap();
+    /// *x += 1;
+    /// assert_eq!(*x, 124);
+    /// ```
+    pub fn try_new(slot: &'static StaticSlot<T>, value: T) -> Option<Self> {
+        Some(Self {
+            ptr: slot.claim(value)?,
+            claimed: &slot.claimed,
+        })
+    }
+}
+
 impl<T: ?Sized> Unique<T> {
     #[doc(hidden)]
-    pub unsafe fn new<U>(buf: *mut u8, val: &mut U, val_ptr: *mut T) -> Self {
+    pub unsafe fn new<U>(
+        buf: *mut u8,
+        val: &mut U,
+        val_ptr: *mut T,
+        claimed: &'static AtomicBool,
+    ) -> Self {
         Self {
             ptr: create_obj(buf, val, val_ptr),
+            claimed,
+        }
+    }
+}
+
+impl<T> Unique<[T]> {
+    #[doc(hidden)]
+    pub unsafe fn new_from_iter<I>(
+        buf: *mut T,
+        cap: usize,
+        iter: I,
+        claimed: &'static AtomicBool,
+    ) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut len = 0;
+        for val in iter.into_iter().take(cap) {
+            buf.add(len).write(val);
+            len += 1;
+        }
+
+        Self {
+            ptr: ptr::slice_from_raw_parts_mut(buf, len),
+            claimed,
+        }
+    }
+}
+
+impl<T: Zeroable> Unique<[T]> {
+    #[doc(hidden)]
+    pub unsafe fn new_zeroed(
+        buf: *mut T,
+        len: usize,
+        dirty: bool,
+        claimed: &'static AtomicBool,
+    ) -> Self {
+        if dirty {
+            ptr::write_bytes(buf, 0, len);
+        }
+
+        Self {
+            ptr: ptr::slice_from_raw_parts_mut(buf, len),
+            claimed,
         }
     }
 }
@@ -347,8 +690,19 @@ impl Unique<dyn Any + Send + Sync + 'static> {
 
 impl<T: ?Sized> Unique<T> {
     unsafe fn downcast_unchecked<U: Any>(self) -> Unique<U> {
-        let Self { ptr } = self;
-        Unique { ptr: ptr as *mut _ }
+        let this = mem::ManuallyDrop::new(self);
+        Unique {
+            ptr: this.ptr as *mut _,
+            claimed: this.claimed,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Unique<T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.ptr) };
+        // the data is gone, so the macro site may claim the static again
+        self.claimed.store(false, atomic::Ordering::Release);
     }
 }
 
@@ -1,6 +1,10 @@
 use core::any::Any;
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
 
-use qptr::{make_static_shared, make_static_unique, Shared, Unique};
+use qptr::{
+    make_static_shared, make_static_unique, make_static_unique_from_iter,
+    make_static_unique_zeroed, Shared, StaticSlot, Unique,
+};
 
 #[test]
 fn shared_make_static() {
@@ -23,9 +27,48 @@ fn unique_make_static() {
 #[test]
 #[should_panic]
 fn unique_already_claimed() {
-    for _ in 0..2 {
-        let _unique = make_static_unique!(|| -> i32 { 123 }).unwrap();
+    let make = || make_static_unique!(|| -> i32 { 123 });
+    let _first: Unique<i32> = make().unwrap();
+    let _second: Unique<i32> = make().unwrap();
+}
+
+#[test]
+fn unique_reclaim_after_drop() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted(i32);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Relaxed);
+        }
     }
+
+    let make = |i| make_static_unique!(|| -> Counted { Counted(i) });
+
+    let first: Unique<Counted> = make(1).unwrap();
+    assert!(make(2).is_none());
+    assert_eq!(DROPS.load(Relaxed), 0);
+
+    drop(first);
+    assert_eq!(DROPS.load(Relaxed), 1);
+
+    let second: Unique<Counted> = make(3).unwrap();
+    assert_eq!(second.0, 3);
+    drop(second);
+    assert_eq!(DROPS.load(Relaxed), 2);
+}
+
+#[test]
+fn unique_reclaim_after_downcast() {
+    let make = || make_static_unique!(|| -> i32 { 123 });
+
+    let unique: Unique<dyn Any> = make().unwrap();
+    let unique: Unique<i32> = unique.downcast().unwrap();
+    assert!(make().is_none());
+
+    drop(unique);
+    assert!(make().is_some());
 }
 
 #[test]
@@ -53,3 +96,96 @@ fn unique_downcast_err() {
     let unique: Unique<dyn Any> = make_static_unique!(|| -> i32 { 123 }).unwrap();
     assert!(unique.downcast::<u32>().is_err());
 }
+
+#[test]
+fn unique_from_iter() {
+    let unique: Unique<[u32]> = make_static_unique_from_iter!([u32; 4], [1, 2, 3, 4]).unwrap();
+    assert_eq!(&*unique, &[1, 2, 3, 4]);
+}
+
+#[test]
+fn unique_from_iter_short() {
+    let unique: Unique<[u32]> = make_static_unique_from_iter!([u32; 4], 1..=2).unwrap();
+    assert_eq!(&*unique, &[1, 2]);
+}
+
+#[test]
+fn unique_from_iter_long() {
+    let unique: Unique<[u32]> = make_static_unique_from_iter!([u32; 4], 1..).unwrap();
+    assert_eq!(&*unique, &[1, 2, 3, 4]);
+}
+
+#[test]
+#[should_panic]
+fn unique_from_iter_already_claimed() {
+    let make = || make_static_unique_from_iter!([u32; 4], 1..);
+    let _first = make().unwrap();
+    let _second = make().unwrap();
+}
+
+#[test]
+fn unique_from_iter_reclaim_after_drop() {
+    let make = |n| make_static_unique_from_iter!([u32; 4], 1..=n);
+
+    let first = make(2).unwrap();
+    assert!(make(3).is_none());
+
+    drop(first);
+    assert_eq!(&*make(3).unwrap(), &[1, 2, 3]);
+}
+
+#[test]
+fn unique_zeroed() {
+    let unique: Unique<[u64]> = make_static_unique_zeroed!([u64; 1 << 16]).unwrap();
+    assert_eq!(unique.len(), 1 << 16);
+    assert!(unique.iter().all(|n| *n == 0));
+}
+
+#[test]
+fn unique_zeroed_reclaim_after_drop() {
+    let make = || make_static_unique_zeroed!([u8; 4]);
+
+    let mut first = make().unwrap();
+    first.copy_from_slice(&[1, 2, 3, 4]);
+    assert!(make().is_none());
+
+    drop(first);
+    assert_eq!(&*make().unwrap(), &[0; 4]);
+}
+
+#[test]
+fn unique_dyn_layout() {
+    use core::fmt::Debug;
+
+    let unique: Unique<dyn Debug> = make_static_unique!(|| -> [u64; 4] { [1, 2, 3, 4] }).unwrap();
+    assert_eq!(core::mem::size_of_val(&*unique), 32);
+    assert_eq!(format!("{:?}", unique), "[1, 2, 3, 4]");
+}
+
+#[test]
+fn shared_try_new() {
+    static SLOT: StaticSlot<[u8; 3]> = StaticSlot::new();
+
+    let value = [1, 2, 3];
+    let shared = Shared::try_new(&SLOT, value).unwrap();
+    let shared2 = Shared::clone(&shared);
+    assert_eq!(*shared2, [1, 2, 3]);
+    assert_eq!(*shared, [1, 2, 3]);
+
+    // shared pointers never release the slot
+    assert!(Shared::try_new(&SLOT, value).is_none());
+}
+
+#[test]
+fn unique_try_new() {
+    static SLOT: StaticSlot<String> = StaticSlot::new();
+
+    let mut unique = Unique::try_new(&SLOT, String::from("a")).unwrap();
+    unique.push('b');
+    assert_eq!(*unique, "ab");
+    assert!(Unique::try_new(&SLOT, String::from("c")).is_none());
+
+    drop(unique);
+    let unique = Unique::try_new(&SLOT, String::from("d")).unwrap();
+    assert_eq!(*unique, "d");
+}